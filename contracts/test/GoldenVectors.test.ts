@@ -0,0 +1,319 @@
+import { expect } from 'chai';
+import { execFileSync } from 'child_process';
+import { readFileSync } from 'fs';
+import { join } from 'path';
+import hre from 'hardhat';
+import { takeSnapshot, type SnapshotRestorer } from '@nomicfoundation/hardhat-network-helpers';
+
+import type { MinerToken, MinerNFT, MinerGame } from '../typechain-types';
+import type { SignerWithAddress } from '@nomicfoundation/hardhat-ethers/signers';
+import type { ContractTransactionReceipt } from 'ethers';
+
+const { ethers } = hre;
+
+const WASM_MODULES = join(__dirname, '../../packages/wasm-modules');
+
+// Written by `tapforge vectors generate`, see packages/wasm-modules/core/src/vectors.rs.
+// Set GOLDEN_VECTORS to replay a freshly generated file instead.
+const VECTORS_FILE = process.env.GOLDEN_VECTORS ?? join(WASM_MODULES, 'vectors/miner-game.json');
+
+// Built in `before` unless TAPFORGE points at a binary already
+const TAPFORGE = process.env.TAPFORGE ?? join(WASM_MODULES, 'target/debug/tapforge');
+
+type GemName = 'RUBY' | 'SAPPHIRE' | 'DIAMOND';
+
+const GEM_IDS: Record<GemName, number> = { RUBY: 1, SAPPHIRE: 2, DIAMOND: 3 };
+
+// `PlayerData` fields, as offsets from the player's slot in `players`
+const LEVEL_OFFSET = 0n;
+const TAPS_SINCE_CRITICAL_OFFSET = 6n;
+
+type SeedInputs =
+  | { path: 'commit_reveal'; player: string; secret: string; nonce: string; block_hash: string }
+  | {
+      path: 'tap_mine';
+      player: string;
+      timestamp: number;
+      prev_randao: string;
+      block_hash: string;
+    };
+
+interface GoldenVector {
+  name: string;
+  config: {
+    base_reward: string;
+    max_taps_per_call: number;
+    pity_threshold: number;
+    pity_increment: number;
+    max_pity_bonus: number;
+    critical_base_chance: number;
+    critical_multipliers: number[];
+    critical_weights: number[];
+    gem_rewards: Record<GemName, { bonus: string; drop_chance: number }>;
+  };
+  input: {
+    seed_inputs: SeedInputs;
+    taps: number;
+    level: number;
+    miner_powers: number[];
+    taps_since_critical: number;
+  };
+  expected: {
+    seed: string;
+    base_tap_reward: string;
+    taps: { gem: GemName | null; gem_bonus: string }[];
+    total_reward: string;
+    has_critical: boolean;
+    player: {
+      total_power: number;
+      pending_rewards: string;
+      total_taps: number;
+      critical_hits: number;
+      taps_since_critical: number;
+    };
+  };
+}
+
+const { vectors }: { vectors: GoldenVector[] } = JSON.parse(readFileSync(VECTORS_FILE, 'utf8'));
+
+/**
+ * The pinned vector played by the Rust mechanics on the block hash the chain
+ * picked, the one seed input no RPC method can force.
+ */
+function replay(vector: GoldenVector, blockHash: string): GoldenVector {
+  const args = ['vectors', 'replay', '--file', VECTORS_FILE, '--name', vector.name];
+  const json = execFileSync(TAPFORGE, [...args, '--block-hash', blockHash, '--format', 'json'], {
+    encoding: 'utf8',
+  });
+  return JSON.parse(json);
+}
+
+/** The seed `revealTap` or `tapMine` hashes out of its inputs */
+function contractSeed(inputs: SeedInputs, blockHash: string): bigint {
+  const seed =
+    inputs.path === 'commit_reveal'
+      ? ethers.solidityPackedKeccak256(
+          ['bytes32', 'uint256', 'uint256', 'address'],
+          [blockHash, inputs.secret, inputs.nonce, inputs.player]
+        )
+      : ethers.solidityPackedKeccak256(
+          ['uint256', 'uint256', 'address', 'bytes32'],
+          [inputs.timestamp, inputs.prev_randao, inputs.player, blockHash]
+        );
+  return BigInt(seed);
+}
+
+describe('Golden Vectors', function () {
+  let minerToken: MinerToken;
+  let minerNFT: MinerNFT;
+  let minerGame: MinerGame;
+  let owner: SignerWithAddress;
+  let deployed: SnapshotRestorer;
+
+  // Storage slot of `players`, found once
+  let playersSlot: bigint;
+
+  before(async function () {
+    if (!process.env.TAPFORGE) {
+      this.timeout(10 * 60 * 1000);
+      execFileSync('cargo', ['build', '--quiet', '--bin', 'tapforge'], {
+        cwd: WASM_MODULES,
+        stdio: 'inherit',
+      });
+    }
+
+    [owner] = await ethers.getSigners();
+
+    const MinerToken = await ethers.getContractFactory('MinerToken');
+    minerToken = await MinerToken.deploy(owner.address);
+    await minerToken.waitForDeployment();
+
+    const MinerNFT = await ethers.getContractFactory('MinerNFT');
+    minerNFT = await MinerNFT.deploy('https://api.tapforge.game/nft/', owner.address);
+    await minerNFT.waitForDeployment();
+
+    const MinerGame = await ethers.getContractFactory('MinerGame');
+    minerGame = await MinerGame.deploy(await minerToken.getAddress(), await minerNFT.getAddress());
+    await minerGame.waitForDeployment();
+
+    const MINTER_ROLE = await minerToken.MINTER_ROLE();
+    await minerToken.grantRole(MINTER_ROLE, await minerGame.getAddress());
+
+    // The owner tops miners up to the vector's powers
+    const GAME_ROLE = await minerNFT.GAME_ROLE();
+    await minerNFT.grantRole(GAME_ROLE, owner.address);
+
+    playersSlot = await findPlayersSlot();
+
+    // Every vector starts from here, so pinned timestamps never go backwards
+    deployed = await takeSnapshot();
+  });
+
+  afterEach(async function () {
+    await deployed.restore();
+  });
+
+  function playerSlot(address: string): bigint {
+    return BigInt(
+      ethers.keccak256(
+        ethers.AbiCoder.defaultAbiCoder().encode(['address', 'uint256'], [address, playersSlot])
+      )
+    );
+  }
+
+  async function setStorage(slot: bigint, value: bigint | number) {
+    await hre.network.provider.send('hardhat_setStorageAt', [
+      await minerGame.getAddress(),
+      ethers.toQuantity(slot),
+      ethers.toBeHex(value, 32),
+    ]);
+  }
+
+  // `players` is private: write a level into each candidate mapping until
+  // `getPlayerData` reads it back
+  async function findPlayersSlot(): Promise<bigint> {
+    const address = await minerGame.getAddress();
+    for (let candidate = 0n; candidate < 32n; candidate++) {
+      playersSlot = candidate;
+      const slot = playerSlot(owner.address) + LEVEL_OFFSET;
+      const previous = await ethers.provider.getStorage(address, slot);
+      await setStorage(slot, 7);
+      const { level } = await minerGame.getPlayerData(owner.address);
+      await setStorage(slot, BigInt(previous));
+      if (level === 7n) {
+        return candidate;
+      }
+    }
+    throw new Error('players mapping not found');
+  }
+
+  async function applyConfig(config: GoldenVector['config']) {
+    // Constants cannot be set, they have to agree
+    expect(await minerGame.BASE_REWARD()).to.equal(BigInt(config.base_reward));
+    expect(await minerGame.MAX_TAPS_PER_CALL()).to.equal(config.max_taps_per_call);
+    expect(await minerGame.PITY_THRESHOLD()).to.equal(config.pity_threshold);
+    expect(await minerGame.PITY_INCREMENT()).to.equal(config.pity_increment);
+    expect(await minerGame.MAX_PITY_BONUS()).to.equal(config.max_pity_bonus);
+    expect(await minerGame.CRITICAL_BASE_CHANCE()).to.equal(config.critical_base_chance);
+    for (const [i, multiplier] of config.critical_multipliers.entries()) {
+      expect(await minerGame.criticalMultipliers(i)).to.equal(multiplier);
+    }
+
+    await minerGame.updateCriticalWeights(config.critical_weights);
+    for (const [gem, reward] of Object.entries(config.gem_rewards)) {
+      await minerGame.updateGemReward(GEM_IDS[gem as GemName], reward.bonus, reward.drop_chance);
+    }
+  }
+
+  async function registerMiners(player: SignerWithAddress, powers: number[]) {
+    for (const power of powers) {
+      const tokenId = await minerNFT.totalSupply();
+      await minerNFT.mintMiner(player.address, 0, ''); // COMMON, power 1
+      if (power > 1) {
+        await minerNFT.upgradeMiner(tokenId, power - 1);
+      }
+      await minerGame.connect(player).registerMiner(tokenId);
+    }
+  }
+
+  // Level and pity counter have no setter, write them into `players`
+  async function setPlayerState(player: string, level: number, tapsSinceCritical: number) {
+    const slot = playerSlot(player);
+    await setStorage(slot + LEVEL_OFFSET, level); // totalPower, the high half, is recomputed
+    await setStorage(slot + TAPS_SINCE_CRITICAL_OFFSET, tapsSinceCritical);
+
+    const playerData = await minerGame.getPlayerData(player);
+    expect(playerData.level).to.equal(level);
+    expect(playerData.tapsSinceCritical).to.equal(tapsSinceCritical);
+  }
+
+  // Sends the vector's taps through the public entry point of its path with
+  // every pinned seed input forced, and returns the receipt together with the
+  // block hash the chain picked
+  async function play(
+    player: SignerWithAddress,
+    vector: GoldenVector
+  ): Promise<{ receipt: ContractTransactionReceipt; blockHash: string }> {
+    const { seed_inputs: inputs, taps } = vector.input;
+    const game = minerGame.connect(player);
+
+    if (inputs.path === 'commit_reveal') {
+      const commitment = ethers.solidityPackedKeccak256(
+        ['address', 'uint256', 'uint256', 'uint128'],
+        [player.address, inputs.secret, inputs.nonce, taps]
+      );
+      const commit = (await (await game.commitTap(commitment, taps)).wait())!;
+
+      // blockhash(commitBlock + 1) is only readable from the block after it
+      await hre.network.provider.send('hardhat_mine', ['0x1']);
+      const receipt = (await (await game.revealTap(inputs.secret, inputs.nonce)).wait())!;
+
+      const { hash } = (await ethers.provider.getBlock(commit.blockNumber + 1))!;
+      return { receipt, blockHash: hash! };
+    }
+
+    await hre.network.provider.send('hardhat_setPrevRandao', [inputs.prev_randao]);
+    await hre.network.provider.send('evm_setNextBlockTimestamp', [inputs.timestamp]);
+    const receipt = (await (await game.tapMine(taps)).wait())!;
+
+    const block = (await ethers.provider.getBlock(receipt.blockNumber))!;
+    expect(block.timestamp).to.equal(inputs.timestamp);
+    expect(BigInt(block.prevRandao!)).to.equal(BigInt(inputs.prev_randao));
+    return { receipt, blockHash: block.parentHash };
+  }
+
+  for (const vector of vectors) {
+    it(`Should replay ${vector.name}`, async function () {
+      const { input, expected: pinned } = vector;
+      const address = ethers.getAddress(input.seed_inputs.player);
+      await hre.network.provider.send('hardhat_setBalance', [address, ethers.toQuantity(10n ** 20n)]);
+      const player = await ethers.getImpersonatedSigner(address);
+
+      await applyConfig(vector.config);
+      await registerMiners(player, input.miner_powers);
+      await setPlayerState(address, input.level, input.taps_since_critical);
+      const before = await minerGame.getPlayerData(address);
+
+      const { receipt, blockHash } = await play(player, vector);
+      const { expected } = replay(vector, blockHash);
+      expect(BigInt(expected.seed)).to.equal(contractSeed(input.seed_inputs, blockHash));
+
+      // Only the taps depend on the block hash
+      expect(expected.base_tap_reward).to.equal(pinned.base_tap_reward);
+      expect(expected.player.total_power).to.equal(pinned.player.total_power);
+      expect(expected.player.total_taps).to.equal(pinned.player.total_taps);
+
+      const events = receipt.logs.map((log) => minerGame.interface.parseLog(log)!);
+      const tapped = events.filter((event) => event.name === 'Tapped');
+      expect(tapped.map((event) => [...event.args])).to.deep.equal([
+        [address, BigInt(input.taps), BigInt(expected.total_reward), expected.has_critical],
+      ]);
+
+      const gems = events
+        .filter((event) => event.name === 'GemFound')
+        .map((event) => [event.args.player, event.args.gemType, event.args.bonus]);
+      expect(gems).to.deep.equal(
+        expected.taps
+          .filter((tap) => tap.gem !== null)
+          .map((tap) => [address, BigInt(GEM_IDS[tap.gem!]), BigInt(tap.gem_bonus)])
+      );
+
+      if (input.seed_inputs.path === 'commit_reveal') {
+        const revealed = events.filter((event) => event.name === 'CommitmentRevealed');
+        expect(revealed.map((event) => [...event.args])).to.deep.equal([
+          [address, BigInt(expected.total_reward)],
+        ]);
+      }
+
+      // The replay counts from a fresh player, the chain from `before`
+      const after = await minerGame.getPlayerData(address);
+      expect(after.totalPower).to.equal(expected.player.total_power);
+      expect(after.pendingRewards - before.pendingRewards).to.equal(
+        BigInt(expected.player.pending_rewards)
+      );
+      expect(after.totalTaps - before.totalTaps).to.equal(expected.player.total_taps);
+      expect(after.criticalHits - before.criticalHits).to.equal(expected.player.critical_hits);
+      expect(after.tapsSinceCritical).to.equal(expected.player.taps_since_critical);
+    });
+  }
+});
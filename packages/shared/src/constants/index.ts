@@ -162,6 +162,49 @@ export const ERROR_MESSAGES = {
   NOT_MINER_OWNER: 'Not the owner of this miner',
   MINER_NOT_REGISTERED: 'Miner not registered',
   UPGRADE_FAILED: 'Upgrade failed',
+  PENDING_COMMITMENT: 'A commitment is already pending',
+  NO_COMMITMENT: 'No commitment to reveal',
+  ALREADY_REVEALED: 'Commitment already revealed',
+  TOO_EARLY: 'Too early to reveal, wait for the next block',
+  BLOCK_HASH_NOT_AVAILABLE: 'Block hash not available yet',
+  TOO_LATE: 'Commitment expired, commit again',
+  INVALID_REVEAL: 'Reveal does not match the commitment',
+  TOTAL_POWER_OVERFLOW: 'Total power overflow',
+  NO_PENDING_REWARDS: 'No pending rewards',
+  DAILY_LIMIT_EXCEEDED: 'Daily mint limit reached',
+  MAX_MINERS_REACHED: 'Maximum number of miners registered',
+  ALREADY_REGISTERED: 'Miner already registered',
+  MAX_LEVEL_REACHED: 'Maximum level reached',
+  PAUSED: 'Game is paused',
+  NONEXISTENT_TOKEN: 'Miner does not exist',
+  ARITHMETIC_OVERFLOW: 'Amount too large, the transaction would revert',
+
+  // Admin Errors
+  INVALID_LIMIT: 'Invalid limit',
+  LIMIT_TOO_HIGH: 'Limit too high',
+  INVALID_GEM: 'Invalid gem',
+  INVALID_CHANCE: 'Invalid chance',
+  BONUS_TOO_HIGH: 'Bonus too high',
+  INVALID_WEIGHTS_LENGTH: 'Invalid number of weights',
+  WEIGHTS_MUST_SUM_TO_100: 'Weights must sum to 100',
+
+  // NFT Errors
+  NFT_MAX_SUPPLY_REACHED: 'All miners have been minted',
+  NFT_NAME_TOO_LONG: 'Miner name is too long',
+  NFT_INVALID_COUNT: 'Invalid number of miners',
+  NFT_ARRAYS_MISMATCH: 'Recipients and rarities do not match',
+  NFT_MAX_SUPPLY_EXCEEDED: 'Not enough miners left to mint',
+  NFT_TOKEN_DOES_NOT_EXIST: 'Miner does not exist',
+  NFT_POWER_OVERFLOW: 'Miner power overflow',
+  NFT_POWER_TOO_HIGH: 'Miner power too high',
+  NFT_NOT_THE_OWNER: 'Not the owner of this miner',
+
+  // Token Errors
+  TOKEN_AMOUNT_EXCEEDS_MAX: 'Amount exceeds the mint limit',
+  TOKEN_MAX_SUPPLY_EXCEEDED: 'MINE max supply reached',
+  TOKEN_EMPTY_ARRAYS: 'Nothing to mint',
+  TOKEN_ARRAYS_LENGTH_MISMATCH: 'Recipients and amounts do not match',
+  TOKEN_ARITHMETIC_OVERFLOW: 'Amount too large, the transaction would revert',
 
   // API Errors
   UNAUTHORIZED: 'Unauthorized',
@@ -174,6 +217,11 @@ export const ERROR_MESSAGES = {
   INVALID_ADDRESS: 'Invalid Ethereum address',
   INVALID_TOKEN_ID: 'Invalid token ID',
   INVALID_AMOUNT: 'Invalid amount',
+  INVALID_SEED: 'Invalid seed',
+  INVALID_HASH: 'Invalid hash',
+  INVALID_SECRET: 'Invalid secret',
+  INVALID_NONCE: 'Invalid nonce',
+  INVALID_LOG_DATA: 'Invalid log data',
 
   // Network Errors
   NETWORK_ERROR: 'Network error',
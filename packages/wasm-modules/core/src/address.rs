@@ -0,0 +1,109 @@
+//! Parsed 20-byte Ethereum address.
+
+use core::fmt;
+use core::str::FromStr;
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::GameError;
+
+/// 20-byte account address, packed as-is by `abi.encodePacked(address)`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Address(pub [u8; 20]);
+
+impl Address {
+    pub const ZERO: Address = Address([0u8; 20]);
+
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0u8; 20]
+    }
+}
+
+/// Anything that is not `0x` followed by 40 hex digits is `InvalidAddress`
+impl FromStr for Address {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix("0x").ok_or(GameError::InvalidAddress)?;
+        if digits.len() != 40 {
+            return Err(GameError::InvalidAddress);
+        }
+
+        let mut bytes = [0u8; 20];
+        hex::decode_to_slice(digits, &mut bytes).map_err(|_| GameError::InvalidAddress)?;
+        Ok(Address(bytes))
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+impl From<[u8; 20]> for Address {
+    fn from(bytes: [u8; 20]) -> Self {
+        Address(bytes)
+    }
+}
+
+impl Serialize for Address {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AddressVisitor;
+
+        impl Visitor<'_> for AddressVisitor {
+            type Value = Address;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a 0x-prefixed 20-byte hex address")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Address, E> {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(AddressVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_address() {
+        let address: Address = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8"
+            .parse()
+            .unwrap();
+
+        assert_eq!(address.0[0], 0x70);
+        assert_eq!(address.0[19], 0xc8);
+        assert_eq!(
+            address.to_string(),
+            "0x70997970c51812dc3a010c7d01b50e0d17dc79c8"
+        );
+    }
+
+    #[test]
+    fn test_reject_malformed_address() {
+        for text in [
+            "70997970c51812dc3a010c7d01b50e0d17dc79c8",
+            "0x1234",
+            "0xzz997970c51812dc3a010c7d01b50e0d17dc79c8",
+        ] {
+            assert_eq!(text.parse::<Address>(), Err(GameError::InvalidAddress));
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! 256-bit token amounts denominated in wei.
+//!
+//! Mirrors the `uint256` reward math of `MinerGame`. Amounts serialize as
+//! decimal strings, so nothing is truncated to a 53-bit float on the way.
+
+use core::fmt;
+#[cfg(test)]
+use core::ops::{Add, AddAssign, Mul, Sub};
+use core::str::FromStr;
+
+use primitive_types::U256;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::GameError;
+
+/// One whole MINE in wei
+pub const WEI_PER_TOKEN: u64 = 1_000_000_000_000_000_000;
+
+/// Token amount in wei, backed by a `U256`
+///
+/// All arithmetic goes through the `checked_*` methods, where the contract
+/// would revert. The panicking operators only exist in unit tests.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TokenAmount(U256);
+
+impl TokenAmount {
+    pub const ZERO: TokenAmount = TokenAmount(U256::zero());
+
+    /// Amount of `wei`
+    pub fn from_wei(wei: U256) -> Self {
+        TokenAmount(wei)
+    }
+
+    /// Amount of whole tokens, i.e. `tokens * 10**18` wei
+    pub fn from_tokens(tokens: u64) -> Self {
+        TokenAmount(U256::from(tokens) * U256::from(WEI_PER_TOKEN))
+    }
+
+    pub fn wei(&self) -> U256 {
+        self.0
+    }
+
+    /// Whole tokens as a float, for estimates only
+    pub fn to_tokens_f64(&self) -> f64 {
+        let wei = self.0 .0.iter().rev().fold(0.0, |acc, limb| {
+            acc * 18_446_744_073_709_551_616.0 + *limb as f64
+        });
+        wei / WEI_PER_TOKEN as f64
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    pub fn checked_add(self, other: TokenAmount) -> Option<TokenAmount> {
+        self.0.checked_add(other.0).map(TokenAmount)
+    }
+
+    pub fn checked_sub(self, other: TokenAmount) -> Option<TokenAmount> {
+        self.0.checked_sub(other.0).map(TokenAmount)
+    }
+
+    pub fn checked_mul(self, factor: impl Into<U256>) -> Option<TokenAmount> {
+        self.0.checked_mul(factor.into()).map(TokenAmount)
+    }
+
+    pub fn saturating_sub(self, other: TokenAmount) -> TokenAmount {
+        TokenAmount(self.0.saturating_sub(other.0))
+    }
+
+    /// Parse a decimal string, or a `0x`-prefixed hex string
+    pub fn parse(value: &str) -> Result<Self, GameError> {
+        crate::commit::parse_word(value)
+            .map(TokenAmount)
+            .ok_or(GameError::InvalidAmount)
+    }
+}
+
+impl From<u64> for TokenAmount {
+    fn from(wei: u64) -> Self {
+        TokenAmount(U256::from(wei))
+    }
+}
+
+impl From<U256> for TokenAmount {
+    fn from(wei: U256) -> Self {
+        TokenAmount(wei)
+    }
+}
+
+impl From<TokenAmount> for U256 {
+    fn from(amount: TokenAmount) -> Self {
+        amount.0
+    }
+}
+
+impl FromStr for TokenAmount {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+#[cfg(test)]
+impl Add for TokenAmount {
+    type Output = TokenAmount;
+
+    fn add(self, other: TokenAmount) -> TokenAmount {
+        TokenAmount(self.0 + other.0)
+    }
+}
+
+#[cfg(test)]
+impl AddAssign for TokenAmount {
+    fn add_assign(&mut self, other: TokenAmount) {
+        self.0 += other.0;
+    }
+}
+
+#[cfg(test)]
+impl Sub for TokenAmount {
+    type Output = TokenAmount;
+
+    fn sub(self, other: TokenAmount) -> TokenAmount {
+        TokenAmount(self.0 - other.0)
+    }
+}
+
+#[cfg(test)]
+impl Mul<u32> for TokenAmount {
+    type Output = TokenAmount;
+
+    fn mul(self, factor: u32) -> TokenAmount {
+        TokenAmount(self.0 * factor)
+    }
+}
+
+#[cfg(test)]
+impl Mul<U256> for TokenAmount {
+    type Output = TokenAmount;
+
+    fn mul(self, factor: U256) -> TokenAmount {
+        TokenAmount(self.0 * factor)
+    }
+}
+
+#[cfg(test)]
+impl core::iter::Sum for TokenAmount {
+    fn sum<I: Iterator<Item = TokenAmount>>(iter: I) -> TokenAmount {
+        iter.fold(TokenAmount::ZERO, Add::add)
+    }
+}
+
+impl Serialize for TokenAmount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TokenAmount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AmountVisitor;
+
+        impl Visitor<'_> for AmountVisitor {
+            type Value = TokenAmount;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a non-negative integer or decimal string in wei")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<TokenAmount, E> {
+                Ok(TokenAmount::from(v))
+            }
+
+            fn visit_u128<E: de::Error>(self, v: u128) -> Result<TokenAmount, E> {
+                Ok(TokenAmount(U256::from(v)))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<TokenAmount, E> {
+                u64::try_from(v)
+                    .map(TokenAmount::from)
+                    .map_err(|_| E::custom(GameError::InvalidAmount))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<TokenAmount, E> {
+                TokenAmount::parse(v).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(AmountVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimal_round_trip() {
+        let max = U256::MAX.to_string();
+        let amount: TokenAmount = max.parse().unwrap();
+
+        assert_eq!(amount.wei(), U256::MAX);
+        assert_eq!(amount.to_string(), max);
+        assert_eq!(TokenAmount::parse("0x0a").unwrap(), TokenAmount::from(10));
+        assert_eq!(TokenAmount::parse("-1"), Err(GameError::InvalidAmount));
+        assert_eq!(TokenAmount::parse(""), Err(GameError::InvalidAmount));
+    }
+
+    #[test]
+    fn test_serde_uses_decimal_strings() {
+        let amount = TokenAmount::from_tokens(2000);
+        let json = serde_json::to_string(&amount).unwrap();
+
+        assert_eq!(json, "\"2000000000000000000000\"");
+        assert_eq!(serde_json::from_str::<TokenAmount>(&json).unwrap(), amount);
+        assert_eq!(
+            serde_json::from_str::<TokenAmount>("42").unwrap(),
+            TokenAmount::from(42)
+        );
+    }
+
+    #[test]
+    fn test_checked_math() {
+        let max = TokenAmount::from(U256::MAX);
+
+        assert_eq!(max.checked_add(TokenAmount::from(1)), None);
+        assert_eq!(max.checked_mul(2u32), None);
+        assert_eq!(TokenAmount::ZERO.checked_sub(TokenAmount::from(1)), None);
+        assert_eq!(
+            TokenAmount::from(20).checked_mul(5u32),
+            Some(TokenAmount::from(100))
+        );
+        assert_eq!(max.checked_mul(U256::one()), Some(max));
+    }
+
+    #[test]
+    fn test_no_overflow_beyond_u64() {
+        // A x50 critical on a 1000-power account
+        let reward = TokenAmount::from_tokens(10) * U256::from(1001) * 50;
+
+        assert_eq!(reward.to_string(), "500500000000000000000000");
+        assert!(reward.wei() > U256::from(u64::MAX));
+    }
+}
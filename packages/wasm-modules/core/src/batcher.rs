@@ -0,0 +1,322 @@
+//! Coalescing raw UI taps into calls `MinerGame` accepts.
+//!
+//! The frontend pushes the taps it collected every `UI_CONFIG.TAP_BATCH_DELAY`
+//! and drains the calls due in the current block. Each call stays within
+//! `MAX_TAPS_PER_CALL`, and each block within `MAX_TAPS_PER_BLOCK` counting
+//! the taps already spent in it. Commit-reveal taps count against the block
+//! they are revealed in, and only one commitment can be pending at a time, so
+//! no commit is drained until the block after the reveal of the previous one.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfig;
+use crate::error::GameError;
+use crate::power::TapPath;
+
+/// How many blocks ahead, current one included, taps may be scheduled
+pub const DEFAULT_HORIZON_BLOCKS: u64 = 5;
+
+/// One `tapMine` or `commitTap` call
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TapCall {
+    pub path: TapPath,
+    pub taps: u16,
+    /// Block the transaction should be sent in
+    pub send_block: u64,
+    /// Block the taps are executed and counted in, the reveal block for commits
+    pub execute_block: u64,
+}
+
+/// What happens to the queued taps if nothing else is pushed
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchPlan {
+    pub calls: Vec<TapCall>,
+    /// Taps in calls sent in the current block
+    pub ready: u64,
+    /// Taps in calls waiting for a later block
+    pub deferred: u64,
+    /// Taps that do not fit within the horizon
+    pub dropped: u64,
+}
+
+pub struct TapBatcher {
+    config: GameConfig,
+    path: TapPath,
+    horizon_blocks: u64,
+    queued: u64,
+    /// Mirror of `blockTaps[player]` for the blocks still ahead
+    block_taps: BTreeMap<u64, u64>,
+    /// Reveal block of the commitment sent last, no commit goes out until after it
+    pending_reveal: Option<u64>,
+}
+
+impl TapBatcher {
+    pub fn new(config: GameConfig, path: TapPath) -> Self {
+        TapBatcher {
+            config,
+            path,
+            horizon_blocks: DEFAULT_HORIZON_BLOCKS,
+            queued: 0,
+            block_taps: BTreeMap::new(),
+            pending_reveal: None,
+        }
+    }
+
+    pub fn set_horizon_blocks(&mut self, horizon_blocks: u64) {
+        self.horizon_blocks = horizon_blocks.max(1);
+    }
+
+    pub fn queued(&self) -> u64 {
+        self.queued
+    }
+
+    /// Add taps collected by the UI
+    pub fn push(&mut self, taps: u64) -> Result<(), GameError> {
+        self.queued = self
+            .queued
+            .checked_add(taps)
+            .ok_or(GameError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Taps already spent in `block_number`, e.g. by a transaction sent elsewhere
+    pub fn record_block_taps(&mut self, block_number: u64, taps: u64) {
+        let spent = self.block_taps.entry(block_number).or_insert(0);
+        *spent = spent.saturating_add(taps);
+    }
+
+    /// Reveal block of the outstanding commitment, `None` when none is pending
+    pub fn pending_reveal(&self) -> Option<u64> {
+        self.pending_reveal
+    }
+
+    /// Commitment mined in `commit_block`, e.g. by a transaction sent elsewhere
+    pub fn record_commit(&mut self, commit_block: u64) {
+        self.pending_reveal = Some(
+            commit_block
+                .saturating_add(self.config.commit_reveal_blocks)
+                .saturating_add(1),
+        );
+    }
+
+    /// `(send_block, execute_block)` slots from `current_block` within the horizon
+    fn slots(&self, current_block: u64) -> Vec<(u64, u64)> {
+        let end = current_block + self.horizon_blocks;
+        match self.path {
+            TapPath::TapMine => (current_block..end).map(|block| (block, block)).collect(),
+            TapPath::CommitReveal => {
+                // The reveal needs the hash of `commitBlock + COMMIT_REVEAL_BLOCKS`,
+                // and a commit ordered before the reveal in its block would revert
+                // with "Pending commitment", so the next one goes out a block later
+                let reveal_delay = self.config.commit_reveal_blocks + 1;
+                let start = self.pending_reveal.map_or(current_block, |reveal| {
+                    reveal.saturating_add(1).max(current_block)
+                });
+                (start..end)
+                    .step_by(reveal_delay as usize + 1)
+                    .map(|block| (block, block + reveal_delay))
+                    .collect()
+            }
+        }
+    }
+
+    /// Split the queue into calls without changing any state
+    pub fn plan(&self, current_block: u64) -> BatchPlan {
+        let mut remaining = self.queued;
+        let mut used = self.block_taps.clone();
+        let mut plan = BatchPlan::default();
+
+        for (send_block, execute_block) in self.slots(current_block) {
+            loop {
+                let spent = used.entry(execute_block).or_insert(0);
+                let capacity = self
+                    .config
+                    .max_taps_per_block
+                    .saturating_sub(*spent)
+                    .min(self.config.max_taps_per_call as u64);
+                let taps = remaining.min(capacity);
+                if taps == 0 {
+                    break;
+                }
+
+                *spent += taps;
+                remaining -= taps;
+                plan.calls.push(TapCall {
+                    path: self.path,
+                    taps: taps as u16,
+                    send_block,
+                    execute_block,
+                });
+                if send_block == current_block {
+                    plan.ready += taps;
+                } else {
+                    plan.deferred += taps;
+                }
+
+                if self.path == TapPath::CommitReveal {
+                    break;
+                }
+            }
+        }
+
+        plan.dropped = remaining;
+        plan
+    }
+
+    /// Take the calls to send in `current_block` off the queue
+    pub fn drain(&mut self, current_block: u64) -> Vec<TapCall> {
+        let calls: Vec<TapCall> = self
+            .plan(current_block)
+            .calls
+            .into_iter()
+            .filter(|call| call.send_block == current_block)
+            .collect();
+
+        self.block_taps.retain(|block, _| *block >= current_block);
+        self.pending_reveal = self
+            .pending_reveal
+            .filter(|reveal| *reveal >= current_block);
+        for call in &calls {
+            self.queued -= call.taps as u64;
+            self.record_block_taps(call.execute_block, call.taps as u64);
+            if call.path == TapPath::CommitReveal {
+                self.pending_reveal = Some(call.execute_block);
+            }
+        }
+        calls
+    }
+
+    /// Forget queued taps that do not fit within the horizon, returns how many
+    pub fn drop_overflow(&mut self, current_block: u64) -> u64 {
+        let dropped = self.plan(current_block).dropped;
+        self.queued -= dropped;
+        dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tap_counts(plan: &BatchPlan, block: u64) -> Vec<u16> {
+        plan.calls
+            .iter()
+            .filter(|call| call.execute_block == block)
+            .map(|call| call.taps)
+            .collect()
+    }
+
+    #[test]
+    fn test_tap_mine_spills_into_next_blocks() {
+        let mut batcher = TapBatcher::new(GameConfig::default(), TapPath::TapMine);
+        batcher.push(250).unwrap();
+
+        let plan = batcher.plan(10);
+        assert_eq!(tap_counts(&plan, 10), vec![20; 5]);
+        assert_eq!(tap_counts(&plan, 11), vec![20; 5]);
+        assert_eq!(tap_counts(&plan, 12), vec![20, 20, 10]);
+        assert_eq!((plan.ready, plan.deferred, plan.dropped), (100, 150, 0));
+
+        batcher.set_horizon_blocks(2);
+        assert_eq!(batcher.plan(10).dropped, 50);
+    }
+
+    #[test]
+    fn test_existing_block_taps_reduce_capacity() {
+        let mut batcher = TapBatcher::new(GameConfig::default(), TapPath::TapMine);
+        batcher.record_block_taps(10, 85);
+        batcher.push(30).unwrap();
+
+        let calls = batcher.drain(10);
+        assert_eq!(calls.iter().map(|c| c.taps).collect::<Vec<_>>(), vec![15]);
+        assert_eq!(batcher.queued(), 15);
+        assert_eq!(batcher.plan(10).calls[0].send_block, 11);
+
+        let calls = batcher.drain(11);
+        assert_eq!(calls.iter().map(|c| c.taps).collect::<Vec<_>>(), vec![15]);
+        assert_eq!(batcher.queued(), 0);
+    }
+
+    #[test]
+    fn test_commit_reveal_one_commitment_per_cycle() {
+        let mut batcher = TapBatcher::new(GameConfig::default(), TapPath::CommitReveal);
+        batcher.push(50).unwrap();
+
+        let plan = batcher.plan(10);
+        let calls: Vec<(u64, u64, u16)> = plan
+            .calls
+            .iter()
+            .map(|c| (c.send_block, c.execute_block, c.taps))
+            .collect();
+        assert_eq!(calls, vec![(10, 12, 20), (13, 15, 20)]);
+        assert_eq!((plan.ready, plan.deferred, plan.dropped), (20, 20, 10));
+
+        batcher.push(30).unwrap();
+        assert_eq!(batcher.drop_overflow(10), 40);
+        assert_eq!(batcher.queued(), 40);
+    }
+
+    #[test]
+    fn test_commit_reveal_waits_for_the_pending_reveal() {
+        let mut batcher = TapBatcher::new(GameConfig::default(), TapPath::CommitReveal);
+        batcher.push(60).unwrap();
+
+        let calls = batcher.drain(100);
+        assert_eq!(calls.len(), 1);
+        assert_eq!((calls[0].taps, calls[0].execute_block), (20, 102));
+        assert_eq!(batcher.pending_reveal(), Some(102));
+
+        // Another commit in 101 would revert with "Pending commitment"
+        assert!(batcher.drain(101).is_empty());
+        assert_eq!(batcher.plan(101).calls[0].send_block, 103);
+        assert_eq!(batcher.queued(), 40);
+
+        // ... and so would one in the reveal block, if ordered before the reveal
+        assert!(batcher.drain(102).is_empty());
+        assert_eq!(batcher.pending_reveal(), Some(102));
+        assert_eq!(batcher.plan(102).calls[0].send_block, 103);
+
+        let calls = batcher.drain(103);
+        assert_eq!(calls.len(), 1);
+        assert_eq!((calls[0].send_block, calls[0].execute_block), (103, 105));
+        assert_eq!(batcher.pending_reveal(), Some(105));
+
+        // A commitment sent elsewhere blocks the slots the same way
+        let mut batcher = TapBatcher::new(GameConfig::default(), TapPath::CommitReveal);
+        batcher.record_commit(200);
+        batcher.push(10).unwrap();
+        assert!(batcher.drain(201).is_empty());
+        assert!(batcher.drain(202).is_empty());
+        assert_eq!(batcher.drain(203).len(), 1);
+    }
+
+    #[test]
+    fn test_push_reverts_on_overflow() {
+        let mut batcher = TapBatcher::new(GameConfig::default(), TapPath::TapMine);
+        batcher.push(u64::MAX - 1).unwrap();
+        assert_eq!(batcher.push(2), Err(GameError::ArithmeticOverflow));
+        assert_eq!(batcher.queued(), u64::MAX - 1);
+    }
+
+    #[test]
+    fn test_planned_calls_are_legal() {
+        let config = GameConfig::default();
+        let mut batcher = TapBatcher::new(config.clone(), TapPath::TapMine);
+        batcher.record_block_taps(3, 99);
+        batcher.push(437).unwrap();
+
+        let plan = batcher.plan(3);
+        let mut per_block: BTreeMap<u64, u64> = BTreeMap::from([(3, 99)]);
+        for call in &plan.calls {
+            assert!(config.check_tap_count(call.taps).is_ok());
+            *per_block.entry(call.execute_block).or_insert(0) += call.taps as u64;
+        }
+        assert!(per_block
+            .values()
+            .all(|taps| *taps <= config.max_taps_per_block));
+        assert_eq!(plan.ready + plan.deferred + plan.dropped, 437);
+    }
+}
@@ -0,0 +1,222 @@
+//! Commitment hashing for `MinerGame.commitTap` / `revealTap`.
+//!
+//! The contract checks
+//! `keccak256(abi.encodePacked(msg.sender, uint256 secret, uint256 nonce, uint128 taps))`,
+//! so the preimage is 20 + 32 + 32 + 16 = 100 bytes with no padding between
+//! fields.
+//!
+//! Secrets and nonces come from `getrandom`, which is the OS generator
+//! natively and `crypto.getRandomValues` in the browser. Without `std`,
+//! draw them yourself and use `CommitmentBundle::new`.
+
+use primitive_types::{H256, U256};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+use crate::address::Address;
+
+/// Length of the packed commitment preimage
+pub const PACKED_COMMITMENT_LEN: usize = 20 + 32 + 32 + 16;
+
+/// Parse a 256-bit word given as `0x`-prefixed hex or as a decimal string
+pub fn parse_word(value: &str) -> Option<U256> {
+    let value = value.trim();
+    match value.strip_prefix("0x") {
+        Some(hex) if !hex.is_empty() && hex.len() <= 64 => U256::from_str_radix(hex, 16).ok(),
+        Some(_) => None,
+        None if !value.is_empty() => U256::from_dec_str(value).ok(),
+        None => None,
+    }
+}
+
+/// Parse a 32-byte hash given as `0x` followed by 64 hex digits
+pub fn parse_hash(value: &str) -> Option<H256> {
+    let digits = value.strip_prefix("0x")?;
+    if digits.len() != 64 {
+        return None;
+    }
+
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(digits, &mut bytes).ok()?;
+    Some(H256(bytes))
+}
+
+/// `abi.encodePacked(address, uint256 secret, uint256 nonce, uint128 taps)`
+pub fn pack_commitment(
+    player: &Address,
+    secret: U256,
+    nonce: U256,
+    taps: u16,
+) -> [u8; PACKED_COMMITMENT_LEN] {
+    let mut packed = [0u8; PACKED_COMMITMENT_LEN];
+    packed[..20].copy_from_slice(player.as_bytes());
+    secret.write_as_big_endian(&mut packed[20..52]);
+    nonce.write_as_big_endian(&mut packed[52..84]);
+    packed[84..].copy_from_slice(&(taps as u128).to_be_bytes());
+    packed
+}
+
+/// Commitment hash accepted by `commitTap` and checked by `revealTap`
+pub fn commitment_hash(player: &Address, secret: U256, nonce: U256, taps: u16) -> H256 {
+    H256::from_slice(&Keccak256::digest(pack_commitment(
+        player, secret, nonce, taps,
+    )))
+}
+
+/// Check a (commitment, secret, nonce, taps) tuple the same way `revealTap` does
+pub fn verify_commitment(
+    commitment: &H256,
+    player: &Address,
+    secret: U256,
+    nonce: U256,
+    taps: u16,
+) -> bool {
+    commitment_hash(player, secret, nonce, taps) == *commitment
+}
+
+/// Everything needed to send `commitTap` now and `revealTap` later
+///
+/// `secret` and `nonce` must stay private until the reveal.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CommitmentBundle {
+    pub player: Address,
+    pub secret: U256,
+    pub nonce: U256,
+    pub taps: u16,
+    pub hash: H256,
+    /// Block number the commitment was created at
+    pub created_at_block: u64,
+    /// Block `commitTap` was mined in, `None` until it is known
+    #[serde(default)]
+    pub committed_at_block: Option<u64>,
+}
+
+impl CommitmentBundle {
+    pub fn new(
+        player: Address,
+        secret: U256,
+        nonce: U256,
+        taps: u16,
+        created_at_block: u64,
+    ) -> Self {
+        CommitmentBundle {
+            player,
+            secret,
+            nonce,
+            taps,
+            hash: commitment_hash(&player, secret, nonce, taps),
+            created_at_block,
+            committed_at_block: None,
+        }
+    }
+
+    /// Bundle with a fresh secret and nonce from the system CSPRNG
+    #[cfg(feature = "std")]
+    pub fn generate(
+        player: Address,
+        taps: u16,
+        created_at_block: u64,
+    ) -> Result<Self, getrandom::Error> {
+        let secret = random_word()?;
+        let nonce = random_word()?;
+        Ok(CommitmentBundle::new(
+            player,
+            secret,
+            nonce,
+            taps,
+            created_at_block,
+        ))
+    }
+
+    pub fn verify(&self) -> bool {
+        verify_commitment(&self.hash, &self.player, self.secret, self.nonce, self.taps)
+    }
+}
+
+/// Uniformly random 256-bit word from the system CSPRNG
+#[cfg(feature = "std")]
+pub fn random_word() -> Result<U256, getrandom::Error> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes)?;
+    Ok(U256::from_big_endian(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player() -> Address {
+        "0x70997970C51812dc3A010C7d01b50e0d17dc79C8"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_packed_layout() {
+        let packed = pack_commitment(&player(), U256::from(0xaa), U256::from(0xbb), 10);
+
+        assert_eq!(&packed[..20], player().as_bytes());
+        assert_eq!(packed[51], 0xaa);
+        assert!(packed[20..51].iter().all(|b| *b == 0));
+        assert_eq!(packed[83], 0xbb);
+        assert!(packed[84..99].iter().all(|b| *b == 0));
+        assert_eq!(packed[99], 10);
+    }
+
+    #[test]
+    fn test_keccak_not_sha3() {
+        // keccak256("") as used by Solidity, which differs from SHA3-256("")
+        let empty = Keccak256::digest([]);
+        assert_eq!(
+            hex::encode(empty),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+    }
+
+    #[test]
+    fn test_verify_commitment() {
+        let secret = parse_word("0x1234").unwrap();
+        let nonce = parse_word("42").unwrap();
+        let hash = commitment_hash(&player(), secret, nonce, 10);
+
+        assert!(verify_commitment(&hash, &player(), secret, nonce, 10));
+        assert!(!verify_commitment(&hash, &player(), secret, nonce, 11));
+        assert!(!verify_commitment(&hash, &Address::ZERO, secret, nonce, 10));
+        assert!(!verify_commitment(&hash, &player(), nonce, secret, 10));
+    }
+
+    #[test]
+    fn test_parse_hash() {
+        let hash = format!("0x{}", "ab".repeat(32));
+
+        assert_eq!(parse_hash(&hash), Some(H256([0xab; 32])));
+        assert_eq!(parse_hash("0xabcd"), None);
+        assert_eq!(parse_hash(&"ab".repeat(32)), None);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_generated_bundle() {
+        let first = CommitmentBundle::generate(player(), 10, 500).unwrap();
+        let second = CommitmentBundle::generate(player(), 10, 500).unwrap();
+
+        assert!(first.verify());
+        assert_eq!(
+            first.hash,
+            commitment_hash(&player(), first.secret, first.nonce, 10)
+        );
+        assert_ne!(first.secret, second.secret);
+        assert_ne!(first.secret, first.nonce);
+        assert_eq!(first.created_at_block, 500);
+    }
+
+    #[test]
+    fn test_bundle_json_round_trip() {
+        let bundle = CommitmentBundle::new(player(), U256::from(7), U256::from(9), 3, 12);
+        let json = serde_json::to_string(&bundle).unwrap();
+        let parsed: CommitmentBundle = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed, bundle);
+        assert!(parsed.verify());
+    }
+}
@@ -0,0 +1,267 @@
+//! Tunable game parameters of `MinerGame`.
+//!
+//! Defaults are the values of the deployed contract. Everything the owner
+//! can change at runtime (`updateCriticalWeights`, `updateGemReward`,
+//! `setDailyLimit`) is plain data here, so a new config can be loaded as
+//! JSON without rebuilding the module.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::amount::TokenAmount;
+use crate::error::GameError;
+use crate::gems::GemType;
+
+/// One entry of the `gemRewards` mapping
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GemReward {
+    pub bonus: TokenAmount,
+    pub drop_chance: u64,
+}
+
+/// The `gemRewards` mapping, keyed like `GEM_CONFIG` in `packages/shared`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct GemRewards {
+    pub ruby: GemReward,
+    pub sapphire: GemReward,
+    pub diamond: GemReward,
+}
+
+impl GemRewards {
+    pub fn get(&self, gem: GemType) -> &GemReward {
+        match gem {
+            GemType::Ruby => &self.ruby,
+            GemType::Sapphire => &self.sapphire,
+            GemType::Diamond => &self.diamond,
+        }
+    }
+}
+
+impl Default for GemRewards {
+    fn default() -> Self {
+        GemRewards {
+            ruby: GemReward {
+                bonus: TokenAmount::from_tokens(100),
+                drop_chance: 70,
+            },
+            sapphire: GemReward {
+                bonus: TokenAmount::from_tokens(500),
+                drop_chance: 25,
+            },
+            diamond: GemReward {
+                bonus: TokenAmount::from_tokens(2000),
+                drop_chance: 5,
+            },
+        }
+    }
+}
+
+/// Every mechanics parameter of `MinerGame`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct GameConfig {
+    pub base_reward: TokenAmount,
+    pub max_taps_per_call: u16,
+    pub max_taps_per_block: u64,
+    pub upgrade_cost_multiplier: TokenAmount,
+    pub pity_threshold: u64,
+    pub pity_increment: u64,
+    pub max_pity_bonus: u64,
+    pub critical_base_chance: u64,
+    pub max_level: u32,
+    pub max_registered_miners: usize,
+    pub commit_reveal_blocks: u64,
+    pub max_reveal_delay: u64,
+    pub max_daily_mint: TokenAmount,
+    pub daily_mint_limit: TokenAmount,
+    /// Percentage applied to the base reward on the commit-reveal path
+    pub commit_reveal_bonus_percent: u64,
+    pub critical_multipliers: [u32; 4],
+    pub critical_weights: [u64; 4],
+    /// Roll below which a critical tap also rolls for a gem
+    pub gem_drop_chance: u64,
+    pub gem_rewards: GemRewards,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            base_reward: TokenAmount::from_tokens(10),
+            max_taps_per_call: 20,
+            max_taps_per_block: 100,
+            upgrade_cost_multiplier: TokenAmount::from_tokens(100),
+            pity_threshold: 50,
+            pity_increment: 2,
+            max_pity_bonus: 50,
+            critical_base_chance: 10,
+            max_level: 100,
+            max_registered_miners: 50,
+            commit_reveal_blocks: 1,
+            max_reveal_delay: 256,
+            max_daily_mint: TokenAmount::from_tokens(1_000_000),
+            daily_mint_limit: TokenAmount::from_tokens(1_000_000),
+            commit_reveal_bonus_percent: 110,
+            critical_multipliers: [2, 5, 10, 50],
+            critical_weights: [60, 25, 10, 5],
+            gem_drop_chance: 5,
+            gem_rewards: GemRewards::default(),
+        }
+    }
+}
+
+/// Summary in the shape of the frontend's `GameParams`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GameParams {
+    pub base_reward: TokenAmount,
+    pub max_taps_per_call: u32,
+    pub max_taps_per_block: u32,
+    pub upgrade_cost_multiplier: TokenAmount,
+    pub pity_threshold: u32,
+    pub critical_weights: Vec<u32>,
+    /// `gem_drop_chance`, in percent of taps
+    pub gem_drop_rate: u32,
+}
+
+impl GameConfig {
+    /// Parse a JSON config; missing fields fall back to the deployed values
+    pub fn from_json(json: &str) -> Result<GameConfig, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// `GameParams` view, plain numbers saturating at `u32::MAX`
+    pub fn params(&self) -> GameParams {
+        let number = |value: u64| u32::try_from(value).unwrap_or(u32::MAX);
+        GameParams {
+            base_reward: self.base_reward,
+            max_taps_per_call: self.max_taps_per_call.into(),
+            max_taps_per_block: number(self.max_taps_per_block),
+            upgrade_cost_multiplier: self.upgrade_cost_multiplier,
+            pity_threshold: number(self.pity_threshold),
+            critical_weights: self.critical_weights.iter().copied().map(number).collect(),
+            gem_drop_rate: number(self.gem_drop_chance),
+        }
+    }
+
+    /// `require(taps > 0 && taps <= MAX_TAPS_PER_CALL)` of `commitTap` and `tapMine`
+    pub fn check_tap_count(&self, taps: u16) -> Result<(), GameError> {
+        if taps == 0 || taps > self.max_taps_per_call {
+            return Err(GameError::InvalidTapCount);
+        }
+        Ok(())
+    }
+
+    /// Apply the same checks as the owner-only setters of `MinerGame`
+    pub fn validate(&self) -> Result<(), GameError> {
+        // No wrapping around, the contract sums in uint256
+        let sum = self
+            .critical_weights
+            .iter()
+            .try_fold(0u64, |sum, weight| sum.checked_add(*weight));
+        if sum != Some(100) {
+            return Err(GameError::WeightsMustSumTo100);
+        }
+        for gem in GemType::ALL {
+            let reward = self.gem_rewards.get(gem);
+            if reward.drop_chance > 100 {
+                return Err(GameError::InvalidChance);
+            }
+            if reward.bonus > TokenAmount::from_tokens(10_000) {
+                return Err(GameError::BonusTooHigh);
+            }
+        }
+        if self.daily_mint_limit.is_zero() {
+            return Err(GameError::InvalidLimit);
+        }
+        if self.daily_mint_limit > self.max_daily_mint {
+            return Err(GameError::LimitTooHigh);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_are_valid() {
+        assert_eq!(GameConfig::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_partial_json_keeps_defaults() {
+        let config = GameConfig::from_json(r#"{"critical_weights": [40, 30, 20, 10]}"#).unwrap();
+
+        assert_eq!(config.critical_weights, [40, 30, 20, 10]);
+        assert_eq!(config.pity_threshold, 50);
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_gem_rewards_from_json() {
+        let json = r#"{"gem_rewards": {
+            "RUBY": {"bonus": "1", "drop_chance": 50},
+            "SAPPHIRE": {"bonus": "2", "drop_chance": 30},
+            "DIAMOND": {"bonus": "3", "drop_chance": 20}
+        }}"#;
+        let config = GameConfig::from_json(json).unwrap();
+
+        assert_eq!(config.gem_rewards.get(GemType::Diamond).drop_chance, 20);
+        assert_eq!(
+            config.gem_rewards.get(GemType::Ruby).bonus,
+            TokenAmount::from(1)
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_admin_reverts() {
+        let config = GameConfig {
+            critical_weights: [60, 25, 10, 6],
+            ..GameConfig::default()
+        };
+        assert_eq!(config.validate(), Err(GameError::WeightsMustSumTo100));
+
+        // Wraps around to 100 in u64
+        let config = GameConfig {
+            critical_weights: [u64::MAX, 101, 0, 0],
+            ..GameConfig::default()
+        };
+        assert_eq!(config.validate(), Err(GameError::WeightsMustSumTo100));
+
+        let mut config = GameConfig::default();
+        config.gem_rewards.diamond.drop_chance = 101;
+        assert_eq!(config.validate(), Err(GameError::InvalidChance));
+
+        let config = GameConfig {
+            daily_mint_limit: TokenAmount::from_tokens(1_000_001),
+            ..GameConfig::default()
+        };
+        assert_eq!(config.validate(), Err(GameError::LimitTooHigh));
+    }
+
+    #[test]
+    fn test_params_shape() {
+        let json = serde_json::to_value(GameConfig::default().params()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "baseReward": "10000000000000000000",
+                "maxTapsPerCall": 20,
+                "maxTapsPerBlock": 100,
+                "upgradeCostMultiplier": "100000000000000000000",
+                "pityThreshold": 50,
+                "criticalWeights": [60, 25, 10, 5],
+                "gemDropRate": 5
+            })
+        );
+
+        let config = GameConfig {
+            max_taps_per_block: u64::MAX,
+            ..GameConfig::default()
+        };
+        assert_eq!(config.params().max_taps_per_block, u32::MAX);
+    }
+}
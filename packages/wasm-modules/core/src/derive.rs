@@ -0,0 +1,217 @@
+//! Commit secrets derived from a wallet signature.
+//!
+//! The player signs `secret_message` once with `personal_sign`. The signature
+//! is the HKDF-SHA256 input key, and each commitment index expands to its own
+//! secret and nonce, so any device with the wallet can rebuild a lost
+//! commitment. Wallets sign deterministically (RFC 6979), which is what makes
+//! the signature reproducible.
+
+use alloc::{format, string::String};
+
+use hkdf::Hkdf;
+use primitive_types::{H256, U256};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::address::Address;
+use crate::commit::CommitmentBundle;
+use crate::tracker::Commitment;
+
+/// Length of an `r || s || v` signature
+pub const SIGNATURE_LEN: usize = 65;
+
+const HKDF_SALT: &[u8] = b"tapforge/commit-secret/v1";
+
+/// Message the player signs to unlock secret derivation
+pub fn secret_message(domain: &str, chain_id: u64, player: &Address) -> String {
+    format!(
+        "TapForge commit secret\n\n\
+         Domain: {domain}\n\
+         Chain ID: {chain_id}\n\
+         Address: {player}\n\n\
+         Signing lets this wallet recover pending commitments on any device. \
+         Only sign this message on {domain}."
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidSignatureError;
+
+impl core::fmt::Display for InvalidSignatureError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Invalid signature")
+    }
+}
+
+impl core::error::Error for InvalidSignatureError {}
+
+/// Check a 65-byte signature and bring `v` to 27/28, since some wallets return 0/1
+pub fn normalize_signature(signature: &[u8]) -> Result<[u8; SIGNATURE_LEN], InvalidSignatureError> {
+    let mut signature: [u8; SIGNATURE_LEN] =
+        signature.try_into().map_err(|_| InvalidSignatureError)?;
+    match signature[64] {
+        0 | 1 => signature[64] += 27,
+        27 | 28 => {}
+        _ => return Err(InvalidSignatureError),
+    }
+    Ok(signature)
+}
+
+/// A commitment found again by `SecretDeriver::recover`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RecoveredCommitment {
+    pub index: u64,
+    pub bundle: CommitmentBundle,
+}
+
+#[derive(Clone)]
+pub struct SecretDeriver {
+    hkdf: Hkdf<Sha256>,
+    chain_id: u64,
+    player: Address,
+}
+
+impl SecretDeriver {
+    /// `signature` is the 65-byte `personal_sign` result over `secret_message`
+    pub fn new(
+        signature: &[u8],
+        chain_id: u64,
+        player: Address,
+    ) -> Result<Self, InvalidSignatureError> {
+        let ikm = normalize_signature(signature)?;
+        Ok(SecretDeriver {
+            hkdf: Hkdf::new(Some(HKDF_SALT), &ikm),
+            chain_id,
+            player,
+        })
+    }
+
+    pub fn player(&self) -> &Address {
+        &self.player
+    }
+
+    /// Secret and nonce of commitment number `index`
+    pub fn derive(&self, index: u64) -> (U256, U256) {
+        let mut info = [0u8; 8 + 20 + 8];
+        info[..8].copy_from_slice(&self.chain_id.to_be_bytes());
+        info[8..28].copy_from_slice(self.player.as_bytes());
+        info[28..].copy_from_slice(&index.to_be_bytes());
+
+        let mut okm = [0u8; 64];
+        self.hkdf
+            .expand(&info, &mut okm)
+            .expect("64 bytes is a valid HKDF-SHA256 length");
+        (
+            U256::from_big_endian(&okm[..32]),
+            U256::from_big_endian(&okm[32..]),
+        )
+    }
+
+    /// Commitment number `index` for `taps`, ready for `commitTap`
+    pub fn bundle(&self, index: u64, taps: u16, created_at_block: u64) -> CommitmentBundle {
+        let (secret, nonce) = self.derive(index);
+        CommitmentBundle::new(self.player, secret, nonce, taps, created_at_block)
+    }
+
+    /// Scan `max_candidates` indices from `start_index` for the one behind `hash`
+    pub fn find_index(
+        &self,
+        hash: &H256,
+        taps: u16,
+        start_index: u64,
+        max_candidates: u64,
+    ) -> Option<u64> {
+        (start_index..start_index.saturating_add(max_candidates))
+            .find(|index| self.bundle(*index, taps, 0).hash == *hash)
+    }
+
+    /// Rebuild the bundle of an on-chain commitment from `getCommitment`
+    pub fn recover(
+        &self,
+        commitment: &Commitment,
+        start_index: u64,
+        max_candidates: u64,
+    ) -> Option<RecoveredCommitment> {
+        let index = self.find_index(
+            &commitment.hash,
+            commitment.taps,
+            start_index,
+            max_candidates,
+        )?;
+        Some(RecoveredCommitment {
+            index,
+            bundle: self.bundle(index, commitment.taps, commitment.block_number),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player() -> Address {
+        "0x70997970C51812dc3A010C7d01b50e0d17dc79C8"
+            .parse()
+            .unwrap()
+    }
+
+    fn deriver(chain_id: u64) -> SecretDeriver {
+        let mut signature = [0x5a; SIGNATURE_LEN];
+        signature[64] = 28;
+        SecretDeriver::new(&signature, chain_id, player()).unwrap()
+    }
+
+    #[test]
+    fn test_derivation_is_deterministic() {
+        let a = deriver(1);
+        let b = deriver(1);
+
+        assert_eq!(a.derive(0), b.derive(0));
+        assert_ne!(a.derive(0), a.derive(1));
+        assert_ne!(a.derive(0), deriver(31337).derive(0));
+
+        let (secret, nonce) = a.derive(3);
+        assert_ne!(secret, nonce);
+        assert!(a.bundle(3, 10, 0).verify());
+    }
+
+    #[test]
+    fn test_signature_v_normalization() {
+        let mut signature = [0x5a; SIGNATURE_LEN];
+        signature[64] = 1;
+        let normalized = SecretDeriver::new(&signature, 1, player()).unwrap();
+        assert_eq!(normalized.derive(0), deriver(1).derive(0));
+
+        signature[64] = 5;
+        assert!(SecretDeriver::new(&signature, 1, player()).is_err());
+        assert!(SecretDeriver::new(&signature[..64], 1, player()).is_err());
+    }
+
+    #[test]
+    fn test_recover_scans_indices() {
+        let deriver = deriver(1);
+        let bundle = deriver.bundle(17, 12, 0);
+        let commitment = Commitment {
+            hash: bundle.hash,
+            block_number: 900,
+            taps: 12,
+            revealed: false,
+        };
+
+        let recovered = deriver.recover(&commitment, 0, 32).unwrap();
+        assert_eq!(recovered.index, 17);
+        assert_eq!(recovered.bundle.secret, bundle.secret);
+        assert_eq!(recovered.bundle.created_at_block, 900);
+
+        assert_eq!(deriver.recover(&commitment, 0, 17), None);
+        assert_eq!(deriver.recover(&commitment, 18, 100), None);
+    }
+
+    #[test]
+    fn test_secret_message_names_chain() {
+        let message = secret_message("tapforge.xyz", 11155111, &player());
+
+        assert!(message.contains("Chain ID: 11155111"));
+        assert!(message.contains("0x70997970c51812dc3a010c7d01b50e0d17dc79c8"));
+    }
+}
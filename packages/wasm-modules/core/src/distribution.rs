@@ -0,0 +1,257 @@
+//! Exact reward distribution of a single tap.
+//!
+//! A tap is decided by two rolls: `seed % 100` for the critical check, the
+//! multiplier and the gem check, and `(seed >> 8) % 100` for the gem kind.
+//! Enumerating both gives every outcome with its exact weight out of
+//! `OUTCOME_SPACE`. Because the multiplier reuses the critical roll, only the
+//! weights below the critical chance can ever be picked, so with the default
+//! config every critical tap is x2.
+
+use alloc::vec::Vec;
+
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+
+use crate::amount::TokenAmount;
+use crate::config::GameConfig;
+use crate::error::GameError;
+use crate::gems::{select_gem, GemType};
+use crate::taps::{critical_chance, multiplier_for_roll};
+
+/// Number of equally likely `(roll, gem roll)` pairs
+pub const OUTCOME_SPACE: u64 = 100 * 100;
+
+/// One possible result of a tap
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RewardOutcome {
+    pub is_critical: bool,
+    pub multiplier: u32,
+    pub gem: Option<GemType>,
+    pub reward: TokenAmount,
+    /// Exact weight out of `OUTCOME_SPACE`
+    pub weight: u64,
+    pub probability: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TapRewardDistribution {
+    pub taps_since_critical: u64,
+    pub critical_chance: u64,
+    pub base_tap_reward: TokenAmount,
+    /// Distinct outcomes, by increasing reward
+    pub outcomes: Vec<RewardOutcome>,
+    /// Expected reward, rounded down to the wei
+    pub expected_reward: TokenAmount,
+    /// Expected reward in MINE
+    pub mean: f64,
+    /// Variance of the reward in MINE squared
+    pub variance: f64,
+    pub standard_deviation: f64,
+}
+
+/// Distribution of the next tap's reward, `base_tap_reward` including any
+/// commit-reveal bonus
+///
+/// Fails with `ArithmeticOverflow` when an outcome overflows `uint256`,
+/// where the contract would revert.
+pub fn tap_reward_distribution(
+    config: &GameConfig,
+    taps_since_critical: u64,
+    base_tap_reward: TokenAmount,
+) -> Result<TapRewardDistribution, GameError> {
+    let chance = critical_chance(config, taps_since_critical);
+    let mut outcomes: Vec<RewardOutcome> = Vec::new();
+    let mut add = |is_critical: bool,
+                   multiplier: u32,
+                   gem: Option<GemType>,
+                   weight: u64|
+     -> Result<(), GameError> {
+        let same = |outcome: &&mut RewardOutcome| {
+            outcome.is_critical == is_critical
+                && outcome.multiplier == multiplier
+                && outcome.gem == gem
+        };
+        if let Some(outcome) = outcomes.iter_mut().find(same) {
+            outcome.weight += weight;
+            return Ok(());
+        }
+
+        let bonus = gem
+            .map(|gem| config.gem_rewards.get(gem).bonus)
+            .unwrap_or_default();
+        let reward = base_tap_reward
+            .checked_mul(multiplier)
+            .and_then(|reward| reward.checked_add(bonus))
+            .ok_or(GameError::ArithmeticOverflow)?;
+        outcomes.push(RewardOutcome {
+            is_critical,
+            multiplier,
+            gem,
+            reward,
+            weight,
+            probability: 0.0,
+        });
+        Ok(())
+    };
+
+    for roll in 0..100 {
+        if roll >= chance {
+            add(false, 1, None, 100)?;
+            continue;
+        }
+
+        let multiplier = multiplier_for_roll(config, roll);
+        if roll < config.gem_drop_chance {
+            for gem_roll in 0..100 {
+                add(
+                    true,
+                    multiplier,
+                    select_gem(config, U256::from(gem_roll)),
+                    1,
+                )?;
+            }
+        } else {
+            add(true, multiplier, None, 100)?;
+        }
+    }
+
+    outcomes.sort_by_key(|outcome| outcome.reward);
+    let mut total = U256::zero();
+    for outcome in &mut outcomes {
+        outcome.probability = outcome.weight as f64 / OUTCOME_SPACE as f64;
+        total = outcome
+            .reward
+            .wei()
+            .checked_mul(U256::from(outcome.weight))
+            .and_then(|weighted| total.checked_add(weighted))
+            .ok_or(GameError::ArithmeticOverflow)?;
+    }
+
+    let mean: f64 = outcomes
+        .iter()
+        .map(|outcome| outcome.probability * outcome.reward.to_tokens_f64())
+        .sum();
+    let variance: f64 = outcomes
+        .iter()
+        .map(|outcome| {
+            let deviation = outcome.reward.to_tokens_f64() - mean;
+            outcome.probability * deviation * deviation
+        })
+        .sum();
+
+    Ok(TapRewardDistribution {
+        taps_since_critical,
+        critical_chance: chance,
+        base_tap_reward,
+        outcomes,
+        expected_reward: TokenAmount::from_wei(total / OUTCOME_SPACE),
+        mean,
+        variance,
+        standard_deviation: libm::sqrt(variance),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::power::{base_tap_reward, TapPath};
+
+    fn rewards(distribution: &TapRewardDistribution) -> Vec<(u64, u64)> {
+        distribution
+            .outcomes
+            .iter()
+            .map(|o| (o.reward.to_tokens_f64() as u64, o.weight))
+            .collect()
+    }
+
+    #[test]
+    fn test_default_distribution_by_hand() {
+        let config = GameConfig::default();
+        let distribution =
+            tap_reward_distribution(&config, 0, TokenAmount::from_tokens(10)).unwrap();
+
+        // 90% plain, 10% x2 of which half also roll a 70/25/5 gem
+        assert_eq!(
+            rewards(&distribution),
+            vec![(10, 9000), (20, 500), (120, 350), (520, 125), (2020, 25)]
+        );
+        assert_eq!(
+            distribution.outcomes.iter().map(|o| o.weight).sum::<u64>(),
+            OUTCOME_SPACE
+        );
+        // 1.1 * 10 + 0.05 * (0.7 * 100 + 0.25 * 500 + 0.05 * 2000)
+        assert_eq!(
+            distribution.expected_reward,
+            TokenAmount::from_wei(U256::from(25_750u64) * U256::exp10(15))
+        );
+        assert!((distribution.mean - 25.75).abs() < 1e-9);
+        // E[X^2] = 14195
+        assert!((distribution.variance - (14_195.0 - 25.75 * 25.75)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_multiplier_shares_the_critical_roll() {
+        // Even at full pity the chance stays under the x2 weight
+        let config = GameConfig::default();
+        let distribution =
+            tap_reward_distribution(&config, 1_000, TokenAmount::from_tokens(1)).unwrap();
+        assert_eq!(distribution.critical_chance, 60);
+        assert!(distribution
+            .outcomes
+            .iter()
+            .all(|o| !o.is_critical || o.multiplier == 2));
+
+        let always = GameConfig {
+            critical_base_chance: 100,
+            gem_drop_chance: 0,
+            ..GameConfig::default()
+        };
+        let distribution =
+            tap_reward_distribution(&always, 0, TokenAmount::from_tokens(1)).unwrap();
+        assert_eq!(
+            rewards(&distribution),
+            vec![(2, 6000), (5, 2500), (10, 1000), (50, 500)]
+        );
+        assert!((distribution.mean - 5.95).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_commit_reveal_bonus() {
+        let config = GameConfig::default();
+        let plain = tap_reward_distribution(
+            &config,
+            0,
+            base_tap_reward(&config, 9, TapPath::TapMine).unwrap(),
+        )
+        .unwrap();
+        let committed = tap_reward_distribution(
+            &config,
+            0,
+            base_tap_reward(&config, 9, TapPath::CommitReveal).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(plain.outcomes[0].reward, TokenAmount::from_tokens(100));
+        assert_eq!(committed.outcomes[0].reward, TokenAmount::from_tokens(110));
+        // Only the tap reward gets the bonus, not the gems
+        assert!((committed.mean - plain.mean - 1.1 * 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_overflowing_outcome_reverts() {
+        let config = GameConfig::default();
+        let huge = TokenAmount::from(U256::MAX / 2);
+
+        // Plain taps fit, x2 crits do not
+        assert_eq!(
+            tap_reward_distribution(&config, 0, huge),
+            Err(GameError::ArithmeticOverflow)
+        );
+        // Every outcome fits, their weighted sum does not
+        let big = TokenAmount::from(U256::MAX / 1_000);
+        assert_eq!(
+            tap_reward_distribution(&config, 0, big),
+            Err(GameError::ArithmeticOverflow)
+        );
+    }
+}
@@ -0,0 +1,316 @@
+//! Revert reasons of `MinerGame`, `MinerNFT` and `MinerToken`.
+//!
+//! Every simulated action that would revert on chain fails with the matching
+//! `GameError`, and malformed inputs fail with one of the input errors that
+//! never reach a contract. Across the WASM boundary the error message is the
+//! `code()`, which is also the key of the entry in `ERROR_MESSAGES` in
+//! `packages/shared`.
+
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum GameError {
+    // MinerGame
+    InvalidAddress,
+    InvalidTapCount,
+    PendingCommitment,
+    NoCommitment,
+    AlreadyRevealed,
+    TooEarly,
+    BlockHashNotAvailable,
+    TooLate,
+    InvalidReveal,
+    #[serde(rename = "TOO_MANY_TAPS")]
+    BlockLimitExceeded,
+    TotalPowerOverflow,
+    NoPendingRewards,
+    DailyLimitExceeded,
+    #[serde(rename = "NOT_MINER_OWNER")]
+    NotTheOwner,
+    MaxMinersReached,
+    AlreadyRegistered,
+    MinerNotRegistered,
+    MaxLevelReached,
+    InsufficientBalance,
+    InsufficientAllowance,
+    InvalidLimit,
+    LimitTooHigh,
+    InvalidGem,
+    InvalidChance,
+    BonusTooHigh,
+    InvalidWeightsLength,
+    #[serde(rename = "WEIGHTS_MUST_SUM_TO_100")]
+    WeightsMustSumTo100,
+    Paused,
+    NonexistentToken,
+    /// Checked arithmetic of any of the contracts
+    ArithmeticOverflow,
+    // MinerNFT
+    NftMaxSupplyReached,
+    NftNameTooLong,
+    NftInvalidCount,
+    NftArraysMismatch,
+    NftMaxSupplyExceeded,
+    NftTokenDoesNotExist,
+    NftPowerOverflow,
+    NftPowerTooHigh,
+    NftNotTheOwner,
+    // MinerToken
+    TokenAmountExceedsMax,
+    TokenMaxSupplyExceeded,
+    TokenEmptyArrays,
+    TokenArraysLengthMismatch,
+    TokenArithmeticOverflow,
+    // Inputs
+    InvalidAmount,
+    InvalidSeed,
+    InvalidHash,
+    InvalidSecret,
+    InvalidNonce,
+    InvalidLogData,
+}
+
+impl GameError {
+    pub const ALL: [GameError; 50] = [
+        GameError::InvalidAddress,
+        GameError::InvalidTapCount,
+        GameError::PendingCommitment,
+        GameError::NoCommitment,
+        GameError::AlreadyRevealed,
+        GameError::TooEarly,
+        GameError::BlockHashNotAvailable,
+        GameError::TooLate,
+        GameError::InvalidReveal,
+        GameError::BlockLimitExceeded,
+        GameError::TotalPowerOverflow,
+        GameError::NoPendingRewards,
+        GameError::DailyLimitExceeded,
+        GameError::NotTheOwner,
+        GameError::MaxMinersReached,
+        GameError::AlreadyRegistered,
+        GameError::MinerNotRegistered,
+        GameError::MaxLevelReached,
+        GameError::InsufficientBalance,
+        GameError::InsufficientAllowance,
+        GameError::InvalidLimit,
+        GameError::LimitTooHigh,
+        GameError::InvalidGem,
+        GameError::InvalidChance,
+        GameError::BonusTooHigh,
+        GameError::InvalidWeightsLength,
+        GameError::WeightsMustSumTo100,
+        GameError::Paused,
+        GameError::NonexistentToken,
+        GameError::ArithmeticOverflow,
+        GameError::NftMaxSupplyReached,
+        GameError::NftNameTooLong,
+        GameError::NftInvalidCount,
+        GameError::NftArraysMismatch,
+        GameError::NftMaxSupplyExceeded,
+        GameError::NftTokenDoesNotExist,
+        GameError::NftPowerOverflow,
+        GameError::NftPowerTooHigh,
+        GameError::NftNotTheOwner,
+        GameError::TokenAmountExceedsMax,
+        GameError::TokenMaxSupplyExceeded,
+        GameError::TokenEmptyArrays,
+        GameError::TokenArraysLengthMismatch,
+        GameError::TokenArithmeticOverflow,
+        GameError::InvalidAmount,
+        GameError::InvalidSeed,
+        GameError::InvalidHash,
+        GameError::InvalidSecret,
+        GameError::InvalidNonce,
+        GameError::InvalidLogData,
+    ];
+
+    /// Stable identifier, shared with the `ERROR_MESSAGES` keys
+    pub fn code(&self) -> &'static str {
+        match self {
+            GameError::InvalidAddress => "INVALID_ADDRESS",
+            GameError::InvalidTapCount => "INVALID_TAP_COUNT",
+            GameError::PendingCommitment => "PENDING_COMMITMENT",
+            GameError::NoCommitment => "NO_COMMITMENT",
+            GameError::AlreadyRevealed => "ALREADY_REVEALED",
+            GameError::TooEarly => "TOO_EARLY",
+            GameError::BlockHashNotAvailable => "BLOCK_HASH_NOT_AVAILABLE",
+            GameError::TooLate => "TOO_LATE",
+            GameError::InvalidReveal => "INVALID_REVEAL",
+            GameError::BlockLimitExceeded => "TOO_MANY_TAPS",
+            GameError::TotalPowerOverflow => "TOTAL_POWER_OVERFLOW",
+            GameError::NoPendingRewards => "NO_PENDING_REWARDS",
+            GameError::DailyLimitExceeded => "DAILY_LIMIT_EXCEEDED",
+            GameError::NotTheOwner => "NOT_MINER_OWNER",
+            GameError::MaxMinersReached => "MAX_MINERS_REACHED",
+            GameError::AlreadyRegistered => "ALREADY_REGISTERED",
+            GameError::MinerNotRegistered => "MINER_NOT_REGISTERED",
+            GameError::MaxLevelReached => "MAX_LEVEL_REACHED",
+            GameError::InsufficientBalance => "INSUFFICIENT_BALANCE",
+            GameError::InsufficientAllowance => "INSUFFICIENT_ALLOWANCE",
+            GameError::InvalidLimit => "INVALID_LIMIT",
+            GameError::LimitTooHigh => "LIMIT_TOO_HIGH",
+            GameError::InvalidGem => "INVALID_GEM",
+            GameError::InvalidChance => "INVALID_CHANCE",
+            GameError::BonusTooHigh => "BONUS_TOO_HIGH",
+            GameError::InvalidWeightsLength => "INVALID_WEIGHTS_LENGTH",
+            GameError::WeightsMustSumTo100 => "WEIGHTS_MUST_SUM_TO_100",
+            GameError::Paused => "PAUSED",
+            GameError::NonexistentToken => "NONEXISTENT_TOKEN",
+            GameError::ArithmeticOverflow => "ARITHMETIC_OVERFLOW",
+            GameError::NftMaxSupplyReached => "NFT_MAX_SUPPLY_REACHED",
+            GameError::NftNameTooLong => "NFT_NAME_TOO_LONG",
+            GameError::NftInvalidCount => "NFT_INVALID_COUNT",
+            GameError::NftArraysMismatch => "NFT_ARRAYS_MISMATCH",
+            GameError::NftMaxSupplyExceeded => "NFT_MAX_SUPPLY_EXCEEDED",
+            GameError::NftTokenDoesNotExist => "NFT_TOKEN_DOES_NOT_EXIST",
+            GameError::NftPowerOverflow => "NFT_POWER_OVERFLOW",
+            GameError::NftPowerTooHigh => "NFT_POWER_TOO_HIGH",
+            GameError::NftNotTheOwner => "NFT_NOT_THE_OWNER",
+            GameError::TokenAmountExceedsMax => "TOKEN_AMOUNT_EXCEEDS_MAX",
+            GameError::TokenMaxSupplyExceeded => "TOKEN_MAX_SUPPLY_EXCEEDED",
+            GameError::TokenEmptyArrays => "TOKEN_EMPTY_ARRAYS",
+            GameError::TokenArraysLengthMismatch => "TOKEN_ARRAYS_LENGTH_MISMATCH",
+            GameError::TokenArithmeticOverflow => "TOKEN_ARITHMETIC_OVERFLOW",
+            GameError::InvalidAmount => "INVALID_AMOUNT",
+            GameError::InvalidSeed => "INVALID_SEED",
+            GameError::InvalidHash => "INVALID_HASH",
+            GameError::InvalidSecret => "INVALID_SECRET",
+            GameError::InvalidNonce => "INVALID_NONCE",
+            GameError::InvalidLogData => "INVALID_LOG_DATA",
+        }
+    }
+
+    /// Revert reason exactly as emitted by the contract, `None` for input errors
+    ///
+    /// OpenZeppelin custom errors are given by their error name, overflows of
+    /// checked arithmetic by the `Panic` code Solidity reverts with.
+    pub fn reason(&self) -> Option<&'static str> {
+        let reason = match self {
+            GameError::InvalidAddress => "Invalid address",
+            GameError::InvalidTapCount => "Invalid tap count",
+            GameError::PendingCommitment => "Pending commitment",
+            GameError::NoCommitment => "No commitment",
+            GameError::AlreadyRevealed => "Already revealed",
+            GameError::TooEarly => "Too early",
+            GameError::BlockHashNotAvailable => "Block hash not available",
+            GameError::TooLate => "Too late",
+            GameError::InvalidReveal => "Invalid reveal",
+            GameError::BlockLimitExceeded => "Block limit exceeded",
+            GameError::TotalPowerOverflow => "Total power overflow",
+            GameError::NoPendingRewards => "No pending rewards",
+            GameError::DailyLimitExceeded => "Daily limit exceeded",
+            GameError::NotTheOwner => "Not the owner",
+            GameError::MaxMinersReached => "Max miners reached",
+            GameError::AlreadyRegistered => "Already registered",
+            GameError::MinerNotRegistered => "Miner not registered",
+            GameError::MaxLevelReached => "Max level reached",
+            GameError::InsufficientBalance => "Insufficient balance",
+            GameError::InsufficientAllowance => "Insufficient allowance",
+            GameError::InvalidLimit => "Invalid limit",
+            GameError::LimitTooHigh => "Limit too high",
+            GameError::InvalidGem => "Invalid gem",
+            GameError::InvalidChance => "Invalid chance",
+            GameError::BonusTooHigh => "Bonus too high",
+            GameError::InvalidWeightsLength => "Invalid weights length",
+            GameError::WeightsMustSumTo100 => "Weights must sum to 100",
+            GameError::Paused => "EnforcedPause",
+            GameError::NonexistentToken => "ERC721NonexistentToken",
+            GameError::ArithmeticOverflow => "Panic(0x11)",
+            GameError::NftMaxSupplyReached => "MinerNFT: Max supply reached",
+            GameError::NftNameTooLong => "MinerNFT: Name too long",
+            GameError::NftInvalidCount => "MinerNFT: Invalid count",
+            GameError::NftArraysMismatch => "MinerNFT: Arrays mismatch",
+            GameError::NftMaxSupplyExceeded => "MinerNFT: Max supply exceeded",
+            GameError::NftTokenDoesNotExist => "MinerNFT: Token does not exist",
+            GameError::NftPowerOverflow => "MinerNFT: Power overflow",
+            GameError::NftPowerTooHigh => "MinerNFT: Power too high",
+            GameError::NftNotTheOwner => "MinerNFT: Not the owner",
+            GameError::TokenAmountExceedsMax => "MinerToken: Amount exceeds max",
+            GameError::TokenMaxSupplyExceeded => "MinerToken: Max supply exceeded",
+            GameError::TokenEmptyArrays => "MinerToken: Empty arrays",
+            GameError::TokenArraysLengthMismatch => "MinerToken: Arrays length mismatch",
+            GameError::TokenArithmeticOverflow => "MinerToken: Arithmetic overflow",
+            GameError::InvalidAmount
+            | GameError::InvalidSeed
+            | GameError::InvalidHash
+            | GameError::InvalidSecret
+            | GameError::InvalidNonce
+            | GameError::InvalidLogData => return None,
+        };
+        Some(reason)
+    }
+
+    pub fn from_code(code: &str) -> Option<GameError> {
+        GameError::ALL.into_iter().find(|e| e.code() == code)
+    }
+
+    /// Map a revert reason returned by a node back to its error
+    pub fn from_reason(reason: &str) -> Option<GameError> {
+        GameError::ALL
+            .into_iter()
+            .find(|e| e.reason() == Some(reason))
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl core::error::Error for GameError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_and_reasons_are_unique() {
+        for (i, a) in GameError::ALL.iter().enumerate() {
+            for b in &GameError::ALL[i + 1..] {
+                assert_ne!(a.code(), b.code());
+                if a.reason().is_some() {
+                    assert_ne!(a.reason(), b.reason());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_lookups() {
+        for error in GameError::ALL {
+            assert_eq!(GameError::from_code(error.code()), Some(error));
+            if let Some(reason) = error.reason() {
+                assert_eq!(GameError::from_reason(reason), Some(error));
+            }
+        }
+        assert_eq!(GameError::from_reason("Unknown"), None);
+    }
+
+    #[test]
+    fn test_serde_uses_codes() {
+        for error in GameError::ALL {
+            let json = serde_json::to_string(&error).unwrap();
+            assert_eq!(json, format!("\"{}\"", error.code()));
+        }
+    }
+
+    #[test]
+    fn test_every_code_has_a_message() {
+        let constants = include_str!("../../../shared/src/constants/index.ts");
+        let start = constants.find("export const ERROR_MESSAGES").unwrap();
+        let end = start + constants[start..].find("} as const;").unwrap();
+        let messages = &constants[start..end];
+
+        for error in GameError::ALL {
+            assert!(
+                messages.contains(&format!("\n  {}: '", error.code())),
+                "{} missing from ERROR_MESSAGES",
+                error.code()
+            );
+        }
+    }
+}
@@ -0,0 +1,670 @@
+//! Decoder for the logs of `MinerGame`, `MinerNFT` and `MinerToken`.
+//!
+//! `topics[0]` is the keccak of the event signature, enums are encoded as
+//! `uint8` in it. Indexed parameters follow in the other topics, the rest is
+//! ABI-encoded in `data`. The ERC20 and ERC721 `Transfer` share a signature
+//! and are told apart by the indexed `tokenId`: four topics and no data.
+//! Decoding is strict, a log with any other layout is `Malformed`.
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use primitive_types::{H256, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::address::Address;
+use crate::amount::TokenAmount;
+use crate::gems::GemType;
+use crate::nft::Rarity;
+
+/// Every event the decoder knows
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    Tapped,
+    GemFound,
+    Withdrawn,
+    MinerRegistered,
+    MinerUnregistered,
+    PlayerUpgraded,
+    CommitmentMade,
+    CommitmentRevealed,
+    EmergencyPause,
+    DailyLimitUpdated,
+    MinerMinted,
+    MinerUpgraded,
+    MinerRenamed,
+    TokensMinted,
+    TokensBurned,
+    /// `Transfer` of `MinerToken` or `MinerNFT`
+    Transfer,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 16] = [
+        EventKind::Tapped,
+        EventKind::GemFound,
+        EventKind::Withdrawn,
+        EventKind::MinerRegistered,
+        EventKind::MinerUnregistered,
+        EventKind::PlayerUpgraded,
+        EventKind::CommitmentMade,
+        EventKind::CommitmentRevealed,
+        EventKind::EmergencyPause,
+        EventKind::DailyLimitUpdated,
+        EventKind::MinerMinted,
+        EventKind::MinerUpgraded,
+        EventKind::MinerRenamed,
+        EventKind::TokensMinted,
+        EventKind::TokensBurned,
+        EventKind::Transfer,
+    ];
+
+    /// Canonical signature, the preimage of the topic
+    pub fn signature(&self) -> &'static str {
+        match self {
+            EventKind::Tapped => "Tapped(address,uint256,uint256,bool)",
+            EventKind::GemFound => "GemFound(address,uint8,uint256)",
+            EventKind::Withdrawn => "Withdrawn(address,uint256)",
+            EventKind::MinerRegistered => "MinerRegistered(address,uint256)",
+            EventKind::MinerUnregistered => "MinerUnregistered(address,uint256)",
+            EventKind::PlayerUpgraded => "PlayerUpgraded(address,uint256,uint256)",
+            EventKind::CommitmentMade => "CommitmentMade(address,bytes32)",
+            EventKind::CommitmentRevealed => "CommitmentRevealed(address,uint256)",
+            EventKind::EmergencyPause => "EmergencyPause(address)",
+            EventKind::DailyLimitUpdated => "DailyLimitUpdated(uint256)",
+            EventKind::MinerMinted => "MinerMinted(address,uint256,uint8,uint256)",
+            EventKind::MinerUpgraded => "MinerUpgraded(uint256,uint256)",
+            EventKind::MinerRenamed => "MinerRenamed(uint256,string)",
+            EventKind::TokensMinted => "TokensMinted(address,uint256)",
+            EventKind::TokensBurned => "TokensBurned(address,uint256)",
+            EventKind::Transfer => "Transfer(address,address,uint256)",
+        }
+    }
+
+    /// `topics[0]` of the event
+    pub fn topic(&self) -> H256 {
+        TOPICS[*self as usize]
+    }
+
+    pub fn from_topic(topic: &H256) -> Option<EventKind> {
+        TOPICS
+            .iter()
+            .position(|known| known == topic)
+            .map(|index| EventKind::ALL[index])
+    }
+}
+
+/// keccak256 of each `signature`, in the order of `EventKind::ALL`
+const TOPICS: [H256; 16] = [
+    topic("4a14b1dd781e1259e99b31a841f6fc6ca0522fecb011819489e943fa9f559926"),
+    topic("396361e754ef87ab18d3f10f9d1251667da071adc382040fac75da7470e5f093"),
+    topic("7084f5476618d8e60b11ef0d7d3f06914655adb8793e28ff7f018d4c76d505d5"),
+    topic("9f75af45683a8dc8c992aadb62ddd8b1f460ec386a36b794110630db3d138cce"),
+    topic("4579c014bd30541e593b464bf605b97ab66d1cac0f8a1696cbc9a026a48d8300"),
+    topic("8a55fae109b0283f162585828ff13e3cfb7824b9d411baa8abdbcbebc7bef439"),
+    topic("ee562b1a4a1f7c6b557d53568270e0ee22f3d5121affa68f0fa7053c3156ee4b"),
+    topic("a9e7ce04175cc7b0259168ba9070b8c3ed95347f382a3090be3c3e66e4f6d0c8"),
+    topic("7c83004a7e59a8ea03b200186c4dda29a4e144d9844d63dbc1a09acf7dfcd485"),
+    topic("6cd8635c4285386b9de2e59a4c1eaf32ad41f28ae64c308280217d7af51464e0"),
+    topic("a6ba32a8fb075f5f5e8dc8eaf6e8e8714f2bf808b276ebc6935c97d98a67d11b"),
+    topic("163b987db4f8b7842ba2989d1c32ad9e809acad740daba07c2b0326b22aaed65"),
+    topic("2d356d08f1ce71706686bb459cb346d069b3d987e2b499665b080182a6a111e5"),
+    topic("3f2c9d57c068687834f0de942a9babb9e5acab57d516d3480a3c16ee165a4273"),
+    topic("fd38818f5291bf0bb3a2a48aadc06ba8757865d1dabd804585338aab3009dcb6"),
+    topic("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"),
+];
+
+/// Topic from 64 lowercase hex digits, at compile time
+const fn topic(hex: &str) -> H256 {
+    const fn nibble(digit: u8) -> u8 {
+        match digit {
+            b'0'..=b'9' => digit - b'0',
+            b'a'..=b'f' => digit - b'a' + 10,
+            _ => panic!("topic is not lowercase hex"),
+        }
+    }
+
+    let hex = hex.as_bytes();
+    assert!(hex.len() == 64, "topic is not 32 bytes");
+    let mut bytes = [0u8; 32];
+    let mut i = 0;
+    while i < 32 {
+        bytes[i] = (nibble(hex[2 * i]) << 4) | nibble(hex[2 * i + 1]);
+        i += 1;
+    }
+    H256(bytes)
+}
+
+/// An event with its signature and topic
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct EventTopic {
+    pub event: EventKind,
+    pub signature: &'static str,
+    pub topic: H256,
+}
+
+/// A decoded log, tagged with its event name in `event`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "event")]
+pub enum ContractEvent {
+    Tapped {
+        player: Address,
+        taps: u64,
+        reward: TokenAmount,
+        critical: bool,
+    },
+    GemFound {
+        player: Address,
+        gem_type: GemType,
+        bonus: TokenAmount,
+    },
+    Withdrawn {
+        player: Address,
+        amount: TokenAmount,
+    },
+    MinerRegistered {
+        player: Address,
+        token_id: u64,
+    },
+    MinerUnregistered {
+        player: Address,
+        token_id: u64,
+    },
+    PlayerUpgraded {
+        player: Address,
+        new_level: u32,
+        cost: TokenAmount,
+    },
+    CommitmentMade {
+        player: Address,
+        commitment: H256,
+    },
+    CommitmentRevealed {
+        player: Address,
+        reward: TokenAmount,
+    },
+    EmergencyPause {
+        caller: Address,
+    },
+    DailyLimitUpdated {
+        new_limit: TokenAmount,
+    },
+    MinerMinted {
+        to: Address,
+        token_id: u64,
+        rarity: Rarity,
+        power: U256,
+    },
+    MinerUpgraded {
+        token_id: u64,
+        new_power: U256,
+    },
+    MinerRenamed {
+        token_id: u64,
+        new_name: String,
+    },
+    TokensMinted {
+        to: Address,
+        amount: TokenAmount,
+    },
+    TokensBurned {
+        from: Address,
+        amount: TokenAmount,
+    },
+    /// `MinerToken` transfer, mints and burns included
+    Erc20Transfer {
+        from: Address,
+        to: Address,
+        value: TokenAmount,
+    },
+    /// `MinerNFT` transfer, mints and burns included
+    Erc721Transfer {
+        from: Address,
+        to: Address,
+        token_id: u64,
+    },
+}
+
+/// Why a log could not be decoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// Anonymous log, nothing to match
+    NoTopics,
+    /// `topics[0]` of an event the decoder does not know
+    UnknownEvent(H256),
+    /// Known event, but the topics or data do not have its layout
+    Malformed(EventKind),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::NoTopics => f.write_str("Log has no topics"),
+            DecodeError::UnknownEvent(topic) => write!(f, "Unknown event topic {topic:#x}"),
+            DecodeError::Malformed(kind) => write!(f, "Malformed {kind:?} log"),
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// Topics and data of one log, checked against the layout of `kind`
+struct Log<'a> {
+    kind: EventKind,
+    topics: &'a [H256],
+    data: &'a [u8],
+}
+
+impl Log<'_> {
+    fn malformed(&self) -> DecodeError {
+        DecodeError::Malformed(self.kind)
+    }
+
+    /// Require `indexed` topics after `topics[0]` and `words` static data words
+    fn expect_layout(&self, indexed: usize, words: usize) -> Result<(), DecodeError> {
+        if self.topics.len() != indexed + 1 || self.data.len() != words * 32 {
+            return Err(self.malformed());
+        }
+        Ok(())
+    }
+
+    fn topic(&self, index: usize) -> &[u8; 32] {
+        &self.topics[index].0
+    }
+
+    fn word(&self, index: usize) -> Result<&[u8; 32], DecodeError> {
+        self.data
+            .get(index * 32..(index + 1) * 32)
+            .and_then(|word| word.try_into().ok())
+            .ok_or_else(|| self.malformed())
+    }
+
+    fn address(&self, word: &[u8; 32]) -> Result<Address, DecodeError> {
+        if word[..12].iter().any(|byte| *byte != 0) {
+            return Err(self.malformed());
+        }
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&word[12..]);
+        Ok(Address(address))
+    }
+
+    fn uint(&self, word: &[u8; 32]) -> U256 {
+        U256::from_big_endian(word)
+    }
+
+    fn small<T: TryFrom<u64>>(&self, word: &[u8; 32]) -> Result<T, DecodeError> {
+        let value = self.uint(word);
+        if value > U256::from(u64::MAX) {
+            return Err(self.malformed());
+        }
+        T::try_from(value.as_u64()).map_err(|_| self.malformed())
+    }
+
+    fn amount(&self, word: &[u8; 32]) -> TokenAmount {
+        TokenAmount::from_wei(self.uint(word))
+    }
+
+    fn bool(&self, word: &[u8; 32]) -> Result<bool, DecodeError> {
+        match self.small::<u8>(word)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(self.malformed()),
+        }
+    }
+
+    /// A lone dynamic `string`: offset, length, then the padded bytes
+    fn string(&self) -> Result<String, DecodeError> {
+        if self.small::<usize>(self.word(0)?)? != 32 {
+            return Err(self.malformed());
+        }
+        let len: usize = self.small(self.word(1)?)?;
+        if self.data.len() != 64 + len.div_ceil(32) * 32 {
+            return Err(self.malformed());
+        }
+        String::from_utf8(self.data[64..64 + len].to_vec()).map_err(|_| self.malformed())
+    }
+
+    fn decode(&self) -> Result<ContractEvent, DecodeError> {
+        let event = match self.kind {
+            EventKind::Tapped => {
+                self.expect_layout(1, 3)?;
+                ContractEvent::Tapped {
+                    player: self.address(self.topic(1))?,
+                    taps: self.small(self.word(0)?)?,
+                    reward: self.amount(self.word(1)?),
+                    critical: self.bool(self.word(2)?)?,
+                }
+            }
+            EventKind::GemFound => {
+                self.expect_layout(1, 2)?;
+                ContractEvent::GemFound {
+                    player: self.address(self.topic(1))?,
+                    gem_type: GemType::from_id(self.small(self.word(0)?)?)
+                        .ok_or_else(|| self.malformed())?,
+                    bonus: self.amount(self.word(1)?),
+                }
+            }
+            EventKind::Withdrawn => {
+                self.expect_layout(1, 1)?;
+                ContractEvent::Withdrawn {
+                    player: self.address(self.topic(1))?,
+                    amount: self.amount(self.word(0)?),
+                }
+            }
+            EventKind::MinerRegistered => {
+                self.expect_layout(1, 1)?;
+                ContractEvent::MinerRegistered {
+                    player: self.address(self.topic(1))?,
+                    token_id: self.small(self.word(0)?)?,
+                }
+            }
+            EventKind::MinerUnregistered => {
+                self.expect_layout(1, 1)?;
+                ContractEvent::MinerUnregistered {
+                    player: self.address(self.topic(1))?,
+                    token_id: self.small(self.word(0)?)?,
+                }
+            }
+            EventKind::PlayerUpgraded => {
+                self.expect_layout(1, 2)?;
+                ContractEvent::PlayerUpgraded {
+                    player: self.address(self.topic(1))?,
+                    new_level: self.small(self.word(0)?)?,
+                    cost: self.amount(self.word(1)?),
+                }
+            }
+            EventKind::CommitmentMade => {
+                self.expect_layout(1, 1)?;
+                ContractEvent::CommitmentMade {
+                    player: self.address(self.topic(1))?,
+                    commitment: H256(*self.word(0)?),
+                }
+            }
+            EventKind::CommitmentRevealed => {
+                self.expect_layout(1, 1)?;
+                ContractEvent::CommitmentRevealed {
+                    player: self.address(self.topic(1))?,
+                    reward: self.amount(self.word(0)?),
+                }
+            }
+            EventKind::EmergencyPause => {
+                self.expect_layout(1, 0)?;
+                ContractEvent::EmergencyPause {
+                    caller: self.address(self.topic(1))?,
+                }
+            }
+            EventKind::DailyLimitUpdated => {
+                self.expect_layout(0, 1)?;
+                ContractEvent::DailyLimitUpdated {
+                    new_limit: self.amount(self.word(0)?),
+                }
+            }
+            EventKind::MinerMinted => {
+                self.expect_layout(2, 2)?;
+                ContractEvent::MinerMinted {
+                    to: self.address(self.topic(1))?,
+                    token_id: self.small(self.topic(2))?,
+                    rarity: Rarity::from_id(self.small(self.word(0)?)?)
+                        .ok_or_else(|| self.malformed())?,
+                    power: self.uint(self.word(1)?),
+                }
+            }
+            EventKind::MinerUpgraded => {
+                self.expect_layout(1, 1)?;
+                ContractEvent::MinerUpgraded {
+                    token_id: self.small(self.topic(1))?,
+                    new_power: self.uint(self.word(0)?),
+                }
+            }
+            EventKind::MinerRenamed => {
+                if self.topics.len() != 2 {
+                    return Err(self.malformed());
+                }
+                ContractEvent::MinerRenamed {
+                    token_id: self.small(self.topic(1))?,
+                    new_name: self.string()?,
+                }
+            }
+            EventKind::TokensMinted => {
+                self.expect_layout(1, 1)?;
+                ContractEvent::TokensMinted {
+                    to: self.address(self.topic(1))?,
+                    amount: self.amount(self.word(0)?),
+                }
+            }
+            EventKind::TokensBurned => {
+                self.expect_layout(1, 1)?;
+                ContractEvent::TokensBurned {
+                    from: self.address(self.topic(1))?,
+                    amount: self.amount(self.word(0)?),
+                }
+            }
+            EventKind::Transfer if self.topics.len() == 4 => {
+                self.expect_layout(3, 0)?;
+                ContractEvent::Erc721Transfer {
+                    from: self.address(self.topic(1))?,
+                    to: self.address(self.topic(2))?,
+                    token_id: self.small(self.topic(3))?,
+                }
+            }
+            EventKind::Transfer => {
+                self.expect_layout(2, 1)?;
+                ContractEvent::Erc20Transfer {
+                    from: self.address(self.topic(1))?,
+                    to: self.address(self.topic(2))?,
+                    value: self.amount(self.word(0)?),
+                }
+            }
+        };
+        Ok(event)
+    }
+}
+
+/// Decode one log from its raw topics and data
+pub fn decode_log(topics: &[H256], data: &[u8]) -> Result<ContractEvent, DecodeError> {
+    let topic = topics.first().ok_or(DecodeError::NoTopics)?;
+    let kind = EventKind::from_topic(topic).ok_or(DecodeError::UnknownEvent(*topic))?;
+    Log { kind, topics, data }.decode()
+}
+
+/// `topics[0]` of every known event, for log filters
+pub fn event_topics() -> Vec<EventTopic> {
+    EventKind::ALL
+        .into_iter()
+        .map(|kind| EventTopic {
+            event: kind,
+            signature: kind.signature(),
+            topic: kind.topic(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use sha3::{Digest, Keccak256};
+
+    use super::*;
+
+    fn player() -> Address {
+        "0x70997970C51812dc3A010C7d01b50e0d17dc79C8"
+            .parse()
+            .unwrap()
+    }
+
+    fn address_topic(address: &Address) -> H256 {
+        let mut topic = H256::zero();
+        topic.0[12..].copy_from_slice(address.as_bytes());
+        topic
+    }
+
+    fn uint(value: u64) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        word[24..].copy_from_slice(&value.to_be_bytes());
+        word
+    }
+
+    fn data(words: &[[u8; 32]]) -> Vec<u8> {
+        words.concat()
+    }
+
+    #[test]
+    fn test_known_topics() {
+        // keccak256("Transfer(address,address,uint256)")
+        assert_eq!(
+            format!("{:#x}", EventKind::Transfer.topic()),
+            "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+        );
+        let topics = event_topics();
+        assert_eq!(topics.len(), EventKind::ALL.len());
+        for topic in topics {
+            assert_eq!(EventKind::from_topic(&topic.topic), Some(topic.event));
+        }
+    }
+
+    #[test]
+    fn test_topic_table_matches_signatures() {
+        for kind in EventKind::ALL {
+            let hash = Keccak256::digest(kind.signature().as_bytes());
+            assert_eq!(kind.topic(), H256::from_slice(&hash), "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn test_decode_tapped_and_gem() {
+        let topics = [EventKind::Tapped.topic(), address_topic(&player())];
+        let log = data(&[uint(20), uint(1_000), uint(1)]);
+        assert_eq!(
+            decode_log(&topics, &log),
+            Ok(ContractEvent::Tapped {
+                player: player(),
+                taps: 20,
+                reward: TokenAmount::from(1_000),
+                critical: true,
+            })
+        );
+
+        let topics = [EventKind::GemFound.topic(), address_topic(&player())];
+        let log = data(&[uint(3), uint(2_000)]);
+        assert_eq!(
+            decode_log(&topics, &log),
+            Ok(ContractEvent::GemFound {
+                player: player(),
+                gem_type: GemType::Diamond,
+                bonus: TokenAmount::from(2_000),
+            })
+        );
+        // GemType.NONE is never emitted
+        let log = data(&[uint(0), uint(2_000)]);
+        assert_eq!(
+            decode_log(&topics, &log),
+            Err(DecodeError::Malformed(EventKind::GemFound))
+        );
+    }
+
+    #[test]
+    fn test_decode_nft_events() {
+        let topics = [
+            EventKind::MinerMinted.topic(),
+            address_topic(&player()),
+            H256(uint(7)),
+        ];
+        let log = data(&[uint(3), uint(15)]);
+        assert_eq!(
+            decode_log(&topics, &log),
+            Ok(ContractEvent::MinerMinted {
+                to: player(),
+                token_id: 7,
+                rarity: Rarity::Legendary,
+                power: U256::from(15),
+            })
+        );
+
+        let name = b"Digger";
+        let mut padded = [0u8; 32];
+        padded[..name.len()].copy_from_slice(name);
+        let topics = [EventKind::MinerRenamed.topic(), H256(uint(7))];
+        let log = data(&[uint(32), uint(name.len() as u64), padded]);
+        assert_eq!(
+            decode_log(&topics, &log),
+            Ok(ContractEvent::MinerRenamed {
+                token_id: 7,
+                new_name: "Digger".into(),
+            })
+        );
+        assert_eq!(
+            decode_log(&topics, &log[..64]),
+            Err(DecodeError::Malformed(EventKind::MinerRenamed))
+        );
+    }
+
+    #[test]
+    fn test_transfers_by_layout() {
+        let from = Address::ZERO;
+        let erc20 = [
+            EventKind::Transfer.topic(),
+            address_topic(&from),
+            address_topic(&player()),
+        ];
+        assert_eq!(
+            decode_log(&erc20, &uint(5)),
+            Ok(ContractEvent::Erc20Transfer {
+                from,
+                to: player(),
+                value: TokenAmount::from(5),
+            })
+        );
+
+        let mut erc721 = erc20.to_vec();
+        erc721.push(H256(uint(42)));
+        assert_eq!(
+            decode_log(&erc721, &[]),
+            Ok(ContractEvent::Erc721Transfer {
+                from,
+                to: player(),
+                token_id: 42,
+            })
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_layouts() {
+        assert_eq!(decode_log(&[], &[]), Err(DecodeError::NoTopics));
+        let unknown = H256::repeat_byte(1);
+        assert_eq!(
+            decode_log(&[unknown], &[]),
+            Err(DecodeError::UnknownEvent(unknown))
+        );
+
+        let malformed = Err(DecodeError::Malformed(EventKind::Withdrawn));
+        let topics = [EventKind::Withdrawn.topic(), address_topic(&player())];
+        assert_eq!(decode_log(&topics[..1], &uint(1)), malformed);
+        assert_eq!(decode_log(&topics, &uint(1)[..31]), malformed);
+        // Dirty address padding
+        let dirty = [topics[0], H256::repeat_byte(0xff)];
+        assert_eq!(decode_log(&dirty, &uint(1)), malformed);
+
+        // A bool that is neither 0 nor 1
+        let topics = [EventKind::Tapped.topic(), address_topic(&player())];
+        assert_eq!(
+            decode_log(&topics, &data(&[uint(1), uint(1), uint(2)])),
+            Err(DecodeError::Malformed(EventKind::Tapped))
+        );
+    }
+
+    #[test]
+    fn test_events_as_json() {
+        let event = ContractEvent::PlayerUpgraded {
+            player: player(),
+            new_level: 2,
+            cost: TokenAmount::from_tokens(200),
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["event"], "PlayerUpgraded");
+        assert_eq!(json["new_level"], 2);
+        assert_eq!(json["cost"], "200000000000000000000");
+        assert_eq!(
+            serde_json::from_value::<ContractEvent>(json).unwrap(),
+            event
+        );
+    }
+}
@@ -0,0 +1,283 @@
+//! Gem drops, mirroring `MinerGame._selectGem` and the `gemRewards` table.
+
+use alloc::vec;
+
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfig;
+use crate::taps::{critical_chance, pity_saturation, roll};
+
+/// Gem kinds, in the order of `MinerGame.GemType` (after `NONE`)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum GemType {
+    Ruby,
+    Sapphire,
+    Diamond,
+}
+
+impl GemType {
+    pub const ALL: [GemType; 3] = [GemType::Ruby, GemType::Sapphire, GemType::Diamond];
+
+    /// Value of the variant in the Solidity enum, where `NONE = 0`
+    pub fn id(&self) -> u8 {
+        match self {
+            GemType::Ruby => 1,
+            GemType::Sapphire => 2,
+            GemType::Diamond => 3,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<GemType> {
+        GemType::ALL.into_iter().find(|gem| gem.id() == id)
+    }
+}
+
+/// Mirror of `_selectGem`; the contract passes `seed >> 8`
+pub fn select_gem(config: &GameConfig, seed: U256) -> Option<GemType> {
+    let mut random = roll(seed);
+
+    for gem in GemType::ALL {
+        let drop_chance = config.gem_rewards.get(gem).drop_chance;
+        if random < drop_chance {
+            return Some(gem);
+        }
+        random -= drop_chance;
+    }
+
+    None
+}
+
+/// Gem awarded on a critical tap with the given seed, if any
+pub fn gem_for_critical(config: &GameConfig, seed: U256) -> Option<GemType> {
+    if roll(seed) < config.gem_drop_chance {
+        select_gem(config, seed >> 8)
+    } else {
+        None
+    }
+}
+
+/// Probability of finding any gem, and each kind of gem
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct GemProbabilities {
+    pub any: f64,
+    pub ruby: f64,
+    pub sapphire: f64,
+    pub diamond: f64,
+}
+
+impl GemProbabilities {
+    pub fn get(&self, gem: GemType) -> f64 {
+        match gem {
+            GemType::Ruby => self.ruby,
+            GemType::Sapphire => self.sapphire,
+            GemType::Diamond => self.diamond,
+        }
+    }
+
+    fn set(&mut self, gem: Option<GemType>, value: f64) {
+        match gem {
+            None => self.any = value,
+            Some(GemType::Ruby) => self.ruby = value,
+            Some(GemType::Sapphire) => self.sapphire = value,
+            Some(GemType::Diamond) => self.diamond = value,
+        }
+    }
+}
+
+/// Share of gem rolls that `_selectGem` turns into `gem`
+///
+/// The cumulative `dropChance`s are clamped at 100, so a table that sums to
+/// more than 100 starves the later gems exactly like the contract does.
+pub fn gem_selection_share(config: &GameConfig, gem: GemType) -> f64 {
+    let mut lower = 0;
+    for kind in GemType::ALL {
+        let upper = (lower + config.gem_rewards.get(kind).drop_chance).min(100);
+        if kind == gem {
+            return (upper - lower) as f64 / 100.0;
+        }
+        lower = upper;
+    }
+    0.0
+}
+
+/// Probability that one tap in the given pity state critically hits and
+/// drops a gem matching `gem` (`None` meaning any gem)
+///
+/// The gem check reuses the critical roll, so only rolls below both the
+/// critical chance and `gem_drop_chance` qualify.
+fn gem_chance(config: &GameConfig, taps_since_critical: u64, gem: Option<GemType>) -> f64 {
+    let qualifying = critical_chance(config, taps_since_critical)
+        .min(config.gem_drop_chance)
+        .min(100);
+    let share = match gem {
+        Some(gem) => gem_selection_share(config, gem),
+        None => GemType::ALL
+            .iter()
+            .map(|gem| gem_selection_share(config, *gem))
+            .sum(),
+    };
+    qualifying as f64 / 100.0 * share
+}
+
+/// Gem probabilities for the next single tap
+pub fn tap_gem_probabilities(config: &GameConfig, taps_since_critical: u64) -> GemProbabilities {
+    let mut probabilities = GemProbabilities::default();
+    for gem in [
+        None,
+        Some(GemType::Ruby),
+        Some(GemType::Sapphire),
+        Some(GemType::Diamond),
+    ] {
+        probabilities.set(gem, gem_chance(config, taps_since_critical, gem));
+    }
+    probabilities
+}
+
+/// Probability of at least one gem (of each kind) over a batch of `taps`
+///
+/// Tracks the distribution of `tapsSinceCritical` tap by tap, since the
+/// pity bonus changes the critical chance as the batch goes on.
+pub fn batch_gem_probabilities(
+    config: &GameConfig,
+    taps_since_critical: u64,
+    taps: u32,
+) -> GemProbabilities {
+    let cap = pity_saturation(config);
+    let start = taps_since_critical.min(cap) as usize;
+
+    let mut probabilities = GemProbabilities::default();
+    for gem in [
+        None,
+        Some(GemType::Ruby),
+        Some(GemType::Sapphire),
+        Some(GemType::Diamond),
+    ] {
+        // Probability mass per pity state of not having found the gem yet
+        let mut missing = vec![0.0; cap as usize + 1];
+        missing[start] = 1.0;
+
+        for _ in 0..taps {
+            let mut next = vec![0.0; missing.len()];
+            for (state, mass) in missing.iter().enumerate() {
+                if *mass == 0.0 {
+                    continue;
+                }
+                let critical = (critical_chance(config, state as u64).min(100)) as f64 / 100.0;
+                let found = gem_chance(config, state as u64, gem);
+
+                next[0] += mass * (critical - found);
+                next[(state + 1).min(cap as usize)] += mass * (1.0 - critical);
+            }
+            missing = next;
+        }
+
+        probabilities.set(gem, 1.0 - missing.iter().sum::<f64>());
+    }
+    probabilities
+}
+
+/// Gem chances on the next tap and over a batch of `taps`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct GemDropProbabilities {
+    pub per_tap: GemProbabilities,
+    pub batch: GemProbabilities,
+}
+
+pub fn gem_drop_probabilities(
+    config: &GameConfig,
+    taps_since_critical: u64,
+    taps: u32,
+) -> GemDropProbabilities {
+    GemDropProbabilities {
+        per_tap: tap_gem_probabilities(config, taps_since_critical),
+        batch: batch_gem_probabilities(config, taps_since_critical, taps),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_gem_buckets() {
+        let config = GameConfig::default();
+        assert_eq!(select_gem(&config, U256::from(0)), Some(GemType::Ruby));
+        assert_eq!(select_gem(&config, U256::from(69)), Some(GemType::Ruby));
+        assert_eq!(select_gem(&config, U256::from(70)), Some(GemType::Sapphire));
+        assert_eq!(select_gem(&config, U256::from(94)), Some(GemType::Sapphire));
+        assert_eq!(select_gem(&config, U256::from(95)), Some(GemType::Diamond));
+        assert_eq!(select_gem(&config, U256::from(199)), Some(GemType::Diamond));
+    }
+
+    #[test]
+    fn test_gem_uses_shifted_seed() {
+        let config = GameConfig::default();
+        // roll 4 on the seed itself, roll 95 on seed >> 8
+        let seed = (U256::from(195) << 8) + U256::from(84);
+        assert_eq!(roll(seed), 4);
+        assert_eq!(gem_for_critical(&config, seed), Some(GemType::Diamond));
+
+        let no_drop = (U256::from(195) << 8) + U256::from(85);
+        assert_eq!(gem_for_critical(&config, no_drop), None);
+    }
+
+    #[test]
+    fn test_gem_ids_match_solidity_enum() {
+        for gem in GemType::ALL {
+            assert_eq!(GemType::from_id(gem.id()), Some(gem));
+        }
+        assert_eq!(GemType::from_id(0), None);
+    }
+
+    #[test]
+    fn test_tap_probabilities_default_config() {
+        let probabilities = tap_gem_probabilities(&GameConfig::default(), 0);
+
+        assert!((probabilities.any - 0.05).abs() < 1e-12);
+        assert!((probabilities.ruby - 0.035).abs() < 1e-12);
+        assert!((probabilities.sapphire - 0.0125).abs() < 1e-12);
+        assert!((probabilities.diamond - 0.0025).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_batch_probabilities_match_independent_taps() {
+        // With the default table every tap has the same 5% gem chance
+        let batch = batch_gem_probabilities(&GameConfig::default(), 0, 20);
+
+        assert!((batch.any - (1.0 - 0.95f64.powi(20))).abs() < 1e-12);
+        assert!((batch.diamond - (1.0 - 0.9975f64.powi(20))).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_batch_probabilities_follow_pity() {
+        // Gem checks above the base critical chance only pass once pity grows
+        let config = GameConfig {
+            critical_base_chance: 0,
+            gem_drop_chance: 100,
+            ..GameConfig::default()
+        };
+
+        assert_eq!(batch_gem_probabilities(&config, 0, 50).any, 0.0);
+        assert!(batch_gem_probabilities(&config, 0, 52).any > 0.0);
+        assert!((tap_gem_probabilities(&config, 51).any - 0.02).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_selection_share_clamps_at_100() {
+        let mut config = GameConfig::default();
+        config.gem_rewards.sapphire.drop_chance = 40;
+
+        assert!((gem_selection_share(&config, GemType::Sapphire) - 0.30).abs() < 1e-12);
+        assert_eq!(gem_selection_share(&config, GemType::Diamond), 0.0);
+    }
+
+    #[test]
+    fn test_no_gem_when_chances_leave_a_gap() {
+        let mut config = GameConfig::default();
+        config.gem_rewards.diamond.drop_chance = 0;
+
+        assert_eq!(select_gem(&config, U256::from(95)), None);
+    }
+}
@@ -0,0 +1,462 @@
+//! Encrypted storage for pending commitment bundles.
+//!
+//! Bundles are sealed as one ChaCha20-Poly1305 ciphertext under a key
+//! derived from a passphrase (PBKDF2-HMAC-SHA256) or from a wallet
+//! signature (HKDF-SHA256). Only the versioned `EncryptedKeystore` envelope
+//! is meant to reach IndexedDB or localStorage.
+
+use alloc::vec::Vec;
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use primitive_types::H256;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::commit::CommitmentBundle;
+use crate::config::GameConfig;
+use crate::derive::normalize_signature;
+use crate::tracker::{CommitState, CommitTracker, Commitment};
+
+/// Envelope format written by `Keystore::seal`
+pub const KEYSTORE_VERSION: u32 = 1;
+
+/// PBKDF2 rounds for new passphrase keys
+pub const DEFAULT_PBKDF2_ITERATIONS: u32 = 600_000;
+
+/// Fewest PBKDF2 rounds `KeystoreKey::from_passphrase` accepts
+pub const MIN_PBKDF2_ITERATIONS: u32 = 100_000;
+
+const SALT_LEN: usize = 16;
+
+/// ChaCha20-Poly1305 nonce length, see `Keystore::seal_with_nonce`
+pub const NONCE_LEN: usize = 12;
+
+const KEYSTORE_AAD: &[u8] = b"tapforge/keystore/v1";
+
+#[derive(Debug)]
+pub enum KeystoreError {
+    UnsupportedVersion(u32),
+    /// PBKDF2 rounds below `MIN_PBKDF2_ITERATIONS`
+    TooFewIterations(u32),
+    InvalidSignature,
+    /// Wrong passphrase or signature, or a tampered envelope
+    Decryption,
+    Corrupted(serde_json::Error),
+    #[cfg(feature = "std")]
+    Random(getrandom::Error),
+}
+
+impl core::fmt::Display for KeystoreError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            KeystoreError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported keystore version {version}")
+            }
+            KeystoreError::TooFewIterations(iterations) => write!(
+                f,
+                "{iterations} PBKDF2 iterations, at least {MIN_PBKDF2_ITERATIONS} required"
+            ),
+            KeystoreError::InvalidSignature => f.write_str("Invalid signature"),
+            KeystoreError::Decryption => f.write_str("Keystore decryption failed"),
+            KeystoreError::Corrupted(err) => write!(f, "Corrupted keystore: {err}"),
+            #[cfg(feature = "std")]
+            KeystoreError::Random(err) => write!(f, "Random generator failed: {err}"),
+        }
+    }
+}
+
+impl core::error::Error for KeystoreError {}
+
+#[cfg(feature = "std")]
+impl From<getrandom::Error> for KeystoreError {
+    fn from(err: getrandom::Error) -> Self {
+        KeystoreError::Random(err)
+    }
+}
+
+impl From<serde_json::Error> for KeystoreError {
+    fn from(err: serde_json::Error) -> Self {
+        KeystoreError::Corrupted(err)
+    }
+}
+
+/// How the keystore key is derived, stored in the clear next to the ciphertext
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum KdfParams {
+    Pbkdf2Sha256 {
+        #[serde(with = "hex::serde")]
+        salt: [u8; SALT_LEN],
+        iterations: u32,
+    },
+    SignatureHkdf {
+        #[serde(with = "hex::serde")]
+        salt: [u8; SALT_LEN],
+    },
+}
+
+/// Serialized, encrypted keystore
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EncryptedKeystore {
+    pub version: u32,
+    pub kdf: KdfParams,
+    #[serde(with = "hex::serde")]
+    pub nonce: [u8; NONCE_LEN],
+    #[serde(with = "hex::serde")]
+    pub ciphertext: Vec<u8>,
+}
+
+/// Symmetric key together with the parameters that produced it
+#[derive(Clone)]
+pub struct KeystoreKey {
+    key: [u8; 32],
+    kdf: KdfParams,
+}
+
+impl KeystoreKey {
+    /// New passphrase key with a random salt, at least `MIN_PBKDF2_ITERATIONS` rounds
+    #[cfg(feature = "std")]
+    pub fn from_passphrase(passphrase: &str, iterations: u32) -> Result<Self, KeystoreError> {
+        if iterations < MIN_PBKDF2_ITERATIONS {
+            return Err(KeystoreError::TooFewIterations(iterations));
+        }
+        let kdf = KdfParams::Pbkdf2Sha256 {
+            salt: random_salt()?,
+            iterations,
+        };
+        KeystoreKey::derive(kdf, passphrase.as_bytes())
+    }
+
+    /// New key from a 65-byte wallet signature, with a random salt
+    #[cfg(feature = "std")]
+    pub fn from_signature(signature: &[u8]) -> Result<Self, KeystoreError> {
+        let kdf = KdfParams::SignatureHkdf {
+            salt: random_salt()?,
+        };
+        KeystoreKey::derive(kdf, signature)
+    }
+
+    /// Recompute a key from stored parameters and the passphrase or signature
+    pub fn derive(kdf: KdfParams, secret: &[u8]) -> Result<Self, KeystoreError> {
+        let mut key = [0u8; 32];
+        match &kdf {
+            KdfParams::Pbkdf2Sha256 { salt, iterations } => {
+                pbkdf2::pbkdf2_hmac::<Sha256>(secret, salt, *iterations, &mut key);
+            }
+            KdfParams::SignatureHkdf { salt } => {
+                let signature =
+                    normalize_signature(secret).map_err(|_| KeystoreError::InvalidSignature)?;
+                Hkdf::<Sha256>::new(Some(salt), &signature)
+                    .expand(KEYSTORE_AAD, &mut key)
+                    .expect("32 bytes is a valid HKDF-SHA256 length");
+            }
+        }
+        Ok(KeystoreKey { key, kdf })
+    }
+
+    pub fn kdf(&self) -> &KdfParams {
+        &self.kdf
+    }
+}
+
+#[cfg(feature = "std")]
+fn random_salt() -> Result<[u8; SALT_LEN], getrandom::Error> {
+    let mut salt = [0u8; SALT_LEN];
+    getrandom::getrandom(&mut salt)?;
+    Ok(salt)
+}
+
+/// Whether the reveal window of `bundle` has closed at `block_number`
+///
+/// The window opens at the block `commitTap` was mined in, so a bundle whose
+/// commit is not known to be mined never expires.
+pub fn is_expired(config: &GameConfig, bundle: &CommitmentBundle, block_number: u64) -> bool {
+    let Some(committed_at_block) = bundle.committed_at_block else {
+        return false;
+    };
+    let commitment = Commitment {
+        hash: bundle.hash,
+        block_number: committed_at_block,
+        taps: bundle.taps,
+        revealed: false,
+    };
+    CommitTracker::from_commitment(config.clone(), Some(commitment)).state(block_number)
+        == CommitState::Expired
+}
+
+/// Decrypted pending bundles and the key to seal them again
+pub struct Keystore {
+    key: KeystoreKey,
+    entries: Vec<CommitmentBundle>,
+}
+
+impl Keystore {
+    pub fn new(key: KeystoreKey) -> Self {
+        Keystore {
+            key,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Decrypt an envelope with the passphrase bytes or the signature it was sealed with
+    pub fn open(envelope: &EncryptedKeystore, secret: &[u8]) -> Result<Self, KeystoreError> {
+        if envelope.version != KEYSTORE_VERSION {
+            return Err(KeystoreError::UnsupportedVersion(envelope.version));
+        }
+
+        let key = KeystoreKey::derive(envelope.kdf.clone(), secret)?;
+        let plaintext = ChaCha20Poly1305::new(Key::from_slice(&key.key))
+            .decrypt(
+                Nonce::from_slice(&envelope.nonce),
+                Payload {
+                    msg: &envelope.ciphertext,
+                    aad: KEYSTORE_AAD,
+                },
+            )
+            .map_err(|_| KeystoreError::Decryption)?;
+        let entries = serde_json::from_slice(&plaintext)?;
+        Ok(Keystore { key, entries })
+    }
+
+    /// Encrypt all entries under a fresh nonce
+    #[cfg(feature = "std")]
+    pub fn seal(&self) -> Result<EncryptedKeystore, KeystoreError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        getrandom::getrandom(&mut nonce)?;
+        self.seal_with_nonce(nonce)
+    }
+
+    /// Encrypt all entries under `nonce`, which must never repeat for a key
+    pub fn seal_with_nonce(
+        &self,
+        nonce: [u8; NONCE_LEN],
+    ) -> Result<EncryptedKeystore, KeystoreError> {
+        let plaintext = serde_json::to_vec(&self.entries)?;
+        let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&self.key.key))
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &plaintext,
+                    aad: KEYSTORE_AAD,
+                },
+            )
+            .map_err(|_| KeystoreError::Decryption)?;
+
+        Ok(EncryptedKeystore {
+            version: KEYSTORE_VERSION,
+            kdf: self.key.kdf.clone(),
+            nonce,
+            ciphertext,
+        })
+    }
+
+    pub fn entries(&self) -> &[CommitmentBundle] {
+        &self.entries
+    }
+
+    /// Store a bundle, replacing any entry with the same hash
+    pub fn insert(&mut self, bundle: CommitmentBundle) {
+        self.entries.retain(|entry| entry.hash != bundle.hash);
+        self.entries.push(bundle);
+    }
+
+    pub fn get(&self, hash: &H256) -> Option<&CommitmentBundle> {
+        self.entries.iter().find(|entry| entry.hash == *hash)
+    }
+
+    /// Record the block the `commitTap` of a bundle was mined in, false if unknown
+    pub fn mark_committed(&mut self, hash: &H256, block_number: u64) -> bool {
+        match self.entries.iter_mut().find(|entry| entry.hash == *hash) {
+            Some(entry) => {
+                entry.committed_at_block = Some(block_number);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Drop a bundle once it is revealed
+    pub fn remove(&mut self, hash: &H256) -> Option<CommitmentBundle> {
+        let index = self.entries.iter().position(|entry| entry.hash == *hash)?;
+        Some(self.entries.remove(index))
+    }
+
+    /// Bundles that can no longer be revealed at `block_number`
+    pub fn expired(&self, config: &GameConfig, block_number: u64) -> Vec<&CommitmentBundle> {
+        self.entries
+            .iter()
+            .filter(|entry| is_expired(config, entry, block_number))
+            .collect()
+    }
+
+    /// Remove and return every expired bundle
+    pub fn prune(&mut self, config: &GameConfig, block_number: u64) -> Vec<CommitmentBundle> {
+        let (expired, pending) = self
+            .entries
+            .drain(..)
+            .partition(|entry| is_expired(config, entry, block_number));
+        self.entries = pending;
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::derive::SIGNATURE_LEN;
+    use primitive_types::U256;
+
+    #[cfg(feature = "std")]
+    const ITERATIONS: u32 = MIN_PBKDF2_ITERATIONS;
+
+    fn signature() -> [u8; SIGNATURE_LEN] {
+        let mut signature = [0x42; SIGNATURE_LEN];
+        signature[64] = 27;
+        signature
+    }
+
+    fn bundle(secret: u64, created_at_block: u64) -> CommitmentBundle {
+        let player = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8"
+            .parse()
+            .unwrap();
+        CommitmentBundle::new(
+            player,
+            U256::from(secret),
+            U256::from(1),
+            10,
+            created_at_block,
+        )
+    }
+
+    #[cfg(feature = "std")]
+    fn committed(secret: u64, block: u64) -> CommitmentBundle {
+        CommitmentBundle {
+            committed_at_block: Some(block),
+            ..bundle(secret, block)
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_passphrase_round_trip() {
+        let mut keystore =
+            Keystore::new(KeystoreKey::from_passphrase("hunter2", ITERATIONS).unwrap());
+        keystore.insert(bundle(1, 100));
+        keystore.insert(bundle(2, 200));
+
+        let json = serde_json::to_string(&keystore.seal().unwrap()).unwrap();
+        assert!(!json.contains(&hex::encode(bundle(1, 100).hash)));
+
+        let envelope: EncryptedKeystore = serde_json::from_str(&json).unwrap();
+        let opened = Keystore::open(&envelope, b"hunter2").unwrap();
+        assert_eq!(opened.entries(), keystore.entries());
+
+        assert!(matches!(
+            Keystore::open(&envelope, b"hunter3"),
+            Err(KeystoreError::Decryption)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_passphrase_iterations_floor() {
+        assert!(matches!(
+            KeystoreKey::from_passphrase("hunter2", MIN_PBKDF2_ITERATIONS - 1),
+            Err(KeystoreError::TooFewIterations(99_999))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_signature_key_and_tampering() {
+        let signature = signature();
+        let mut keystore = Keystore::new(KeystoreKey::from_signature(&signature).unwrap());
+        keystore.insert(bundle(3, 100));
+
+        let mut envelope = keystore.seal().unwrap();
+        assert_eq!(
+            Keystore::open(&envelope, &signature).unwrap().entries(),
+            keystore.entries()
+        );
+
+        envelope.ciphertext[0] ^= 1;
+        assert!(matches!(
+            Keystore::open(&envelope, &signature),
+            Err(KeystoreError::Decryption)
+        ));
+
+        envelope.version = 2;
+        assert!(matches!(
+            Keystore::open(&envelope, &signature),
+            Err(KeystoreError::UnsupportedVersion(2))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_nonce_changes_per_seal() {
+        let keystore = Keystore::new(KeystoreKey::from_signature(&signature()).unwrap());
+
+        assert_ne!(
+            keystore.seal().unwrap().nonce,
+            keystore.seal().unwrap().nonce
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_prune_follows_reveal_window() {
+        let config = GameConfig::default();
+        let mut keystore = Keystore::new(KeystoreKey::from_signature(&signature()).unwrap());
+        keystore.insert(committed(1, 100));
+        keystore.insert(committed(2, 300));
+
+        assert!(keystore.expired(&config, 356).is_empty());
+        assert_eq!(keystore.expired(&config, 357).len(), 1);
+
+        let pruned = keystore.prune(&config, 357);
+        assert_eq!(pruned, vec![committed(1, 100)]);
+        assert_eq!(keystore.entries(), &[committed(2, 300)]);
+
+        let hash = committed(2, 300).hash;
+        assert!(keystore.get(&hash).is_some());
+        assert_eq!(keystore.remove(&hash), Some(committed(2, 300)));
+        assert!(keystore.entries().is_empty());
+    }
+
+    #[test]
+    fn test_reveal_window_starts_at_the_mined_commit() {
+        let config = GameConfig::default();
+        let kdf = KdfParams::SignatureHkdf { salt: [7; 16] };
+        let mut keystore = Keystore::new(KeystoreKey::derive(kdf, &signature()).unwrap());
+        let hash = bundle(1, 100).hash;
+        keystore.insert(bundle(1, 100));
+
+        // Not mined yet, whatever the block
+        assert!(keystore.prune(&config, 10_000).is_empty());
+
+        // Mined 10 blocks after the bundle was created, revealable until 366
+        assert!(keystore.mark_committed(&hash, 110));
+        assert!(!keystore.mark_committed(&bundle(2, 100).hash, 110));
+        assert!(keystore.prune(&config, 357).is_empty());
+        assert!(keystore.prune(&config, 366).is_empty());
+        assert_eq!(keystore.prune(&config, 367).len(), 1);
+        assert!(keystore.entries().is_empty());
+    }
+
+    #[test]
+    fn test_seal_with_nonce() {
+        let kdf = KdfParams::SignatureHkdf { salt: [7; 16] };
+        let mut keystore = Keystore::new(KeystoreKey::derive(kdf, &signature()).unwrap());
+        keystore.insert(bundle(1, 100));
+
+        let envelope = keystore.seal_with_nonce([9; NONCE_LEN]).unwrap();
+        assert_eq!(envelope.nonce, [9; NONCE_LEN]);
+        assert_eq!(keystore.seal_with_nonce([9; NONCE_LEN]).unwrap(), envelope);
+
+        let opened = Keystore::open(&envelope, &signature()).unwrap();
+        assert_eq!(opened.entries(), &[bundle(1, 100)]);
+    }
+}
@@ -0,0 +1,36 @@
+//! TapForge game mechanics, bit-exact with the `MinerGame` contract, with
+//! no JS glue.
+//!
+//! Nothing here depends on wasm-bindgen, `tap-forge-wasm` exports it to JS.
+//! Without the `std` feature everything builds on `core` and `alloc`, except
+//! what draws from the OS random generator: `CommitmentBundle::generate` and
+//! the keystore calls that pick a salt or nonce.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+pub mod address;
+pub mod amount;
+pub mod batcher;
+pub mod commit;
+pub mod config;
+pub mod derive;
+pub mod distribution;
+pub mod error;
+pub mod events;
+pub mod gems;
+pub mod keystore;
+pub mod limiter;
+pub mod montecarlo;
+pub mod nft;
+pub mod pity;
+pub mod player;
+pub mod power;
+pub mod random;
+pub mod reveal;
+pub mod simulator;
+pub mod taps;
+pub mod tracker;
+pub mod upgrade;
+pub mod vectors;
@@ -0,0 +1,343 @@
+//! Daily mint circuit breaker of `MinerGame.withdraw`.
+//!
+//! The contract keeps one global `todaysMinted` total, reset whenever
+//! `block.timestamp / 1 days` moves past `currentDay`. A withdrawal always
+//! mints the whole `pendingRewards` and reverts with "Daily limit exceeded"
+//! if that would push the total over `dailyMintLimit`. Both counters are
+//! private, so off-chain they are rebuilt from `Withdrawn` events.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::address::Address;
+use crate::amount::TokenAmount;
+use crate::config::GameConfig;
+use crate::error::GameError;
+
+/// `1 days` in Solidity
+pub const SECONDS_PER_DAY: u64 = 86_400;
+
+/// When a withdrawal of a given amount can go through
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum WithdrawAvailability {
+    Now,
+    /// Once the day rolls over, assuming nobody else withdraws first
+    NextDay {
+        at_timestamp: u64,
+        wait_seconds: u64,
+    },
+    /// The amount is above the whole daily limit
+    Never,
+}
+
+/// One `withdraw` call in a contention simulation, in transaction order
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WithdrawRequest {
+    pub player: Address,
+    pub amount: TokenAmount,
+    pub timestamp: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WithdrawOutcome {
+    pub player: Address,
+    pub amount: TokenAmount,
+    pub error: Option<GameError>,
+    /// `todaysMinted` after this call
+    pub todays_minted: TokenAmount,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ContentionReport {
+    pub outcomes: Vec<WithdrawOutcome>,
+    pub minted: TokenAmount,
+    pub reverted: u64,
+    pub reverted_amount: TokenAmount,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DailyMintLimiter {
+    max_daily_mint: TokenAmount,
+    daily_mint_limit: TokenAmount,
+    current_day: u64,
+    todays_minted: TokenAmount,
+}
+
+impl DailyMintLimiter {
+    /// Fresh limiter, as set up by the constructor at `timestamp`
+    pub fn new(config: &GameConfig, timestamp: u64) -> Self {
+        DailyMintLimiter {
+            max_daily_mint: config.max_daily_mint,
+            daily_mint_limit: config.daily_mint_limit,
+            current_day: day_of(timestamp),
+            todays_minted: TokenAmount::ZERO,
+        }
+    }
+
+    pub fn daily_mint_limit(&self) -> TokenAmount {
+        self.daily_mint_limit
+    }
+
+    pub fn current_day(&self) -> u64 {
+        self.current_day
+    }
+
+    pub fn todays_minted(&self) -> TokenAmount {
+        self.todays_minted
+    }
+
+    /// `todaysMinted` as `withdraw` would see it at `timestamp`
+    fn minted_at(&self, timestamp: u64) -> TokenAmount {
+        if day_of(timestamp) != self.current_day {
+            TokenAmount::ZERO
+        } else {
+            self.todays_minted
+        }
+    }
+
+    /// What can still be minted today at `timestamp`
+    pub fn remaining(&self, timestamp: u64) -> TokenAmount {
+        self.daily_mint_limit
+            .saturating_sub(self.minted_at(timestamp))
+    }
+
+    /// The `require`s of `withdraw` for a player with `amount` pending
+    pub fn check(&self, amount: TokenAmount, timestamp: u64) -> Result<(), GameError> {
+        if amount.is_zero() {
+            return Err(GameError::NoPendingRewards);
+        }
+        if amount > self.remaining(timestamp) {
+            return Err(GameError::DailyLimitExceeded);
+        }
+        Ok(())
+    }
+
+    pub fn availability(&self, amount: TokenAmount, timestamp: u64) -> WithdrawAvailability {
+        if amount > self.daily_mint_limit {
+            return WithdrawAvailability::Never;
+        }
+        if amount <= self.remaining(timestamp) {
+            return WithdrawAvailability::Now;
+        }
+
+        let at_timestamp = (day_of(timestamp) + 1) * SECONDS_PER_DAY;
+        WithdrawAvailability::NextDay {
+            at_timestamp,
+            wait_seconds: at_timestamp - timestamp,
+        }
+    }
+
+    /// `withdraw` of `amount` at `timestamp`
+    pub fn withdraw(&mut self, amount: TokenAmount, timestamp: u64) -> Result<(), GameError> {
+        self.check(amount, timestamp)?;
+        self.record_withdrawn(amount, timestamp)
+    }
+
+    /// Apply a `Withdrawn` event without checking it against the limit
+    ///
+    /// Fails with `ArithmeticOverflow` where `todaysMinted += amount` would
+    /// revert, leaving the limiter untouched.
+    pub fn record_withdrawn(
+        &mut self,
+        amount: TokenAmount,
+        timestamp: u64,
+    ) -> Result<(), GameError> {
+        let today = day_of(timestamp);
+        let minted = if today == self.current_day {
+            self.todays_minted
+        } else {
+            TokenAmount::ZERO
+        };
+        self.todays_minted = minted
+            .checked_add(amount)
+            .ok_or(GameError::ArithmeticOverflow)?;
+        self.current_day = today;
+        Ok(())
+    }
+
+    /// `setDailyLimit`
+    pub fn set_daily_mint_limit(&mut self, limit: TokenAmount) -> Result<(), GameError> {
+        if limit.is_zero() {
+            return Err(GameError::InvalidLimit);
+        }
+        if limit > self.max_daily_mint {
+            return Err(GameError::LimitTooHigh);
+        }
+        self.daily_mint_limit = limit;
+        Ok(())
+    }
+
+    /// Run `requests` in order on a copy of the limiter
+    ///
+    /// Fails with `ArithmeticOverflow` when the minted or reverted total
+    /// does not fit in `uint256`.
+    pub fn simulate(&self, requests: &[WithdrawRequest]) -> Result<ContentionReport, GameError> {
+        let mut limiter = self.clone();
+        let mut report = ContentionReport {
+            outcomes: Vec::with_capacity(requests.len()),
+            minted: TokenAmount::ZERO,
+            reverted: 0,
+            reverted_amount: TokenAmount::ZERO,
+        };
+
+        for request in requests {
+            let error = limiter.withdraw(request.amount, request.timestamp).err();
+            let total = match error {
+                None => &mut report.minted,
+                Some(_) => {
+                    report.reverted += 1;
+                    &mut report.reverted_amount
+                }
+            };
+            *total = total
+                .checked_add(request.amount)
+                .ok_or(GameError::ArithmeticOverflow)?;
+            report.outcomes.push(WithdrawOutcome {
+                player: request.player,
+                amount: request.amount,
+                error,
+                todays_minted: limiter.todays_minted,
+            });
+        }
+        Ok(report)
+    }
+}
+
+/// `timestamp / 1 days`
+pub fn day_of(timestamp: u64) -> u64 {
+    timestamp / SECONDS_PER_DAY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY_10: u64 = 10 * SECONDS_PER_DAY;
+
+    fn tokens(amount: u64) -> TokenAmount {
+        TokenAmount::from_tokens(amount)
+    }
+
+    #[test]
+    fn test_limit_resets_on_day_boundary() {
+        let mut limiter = DailyMintLimiter::new(&GameConfig::default(), DAY_10);
+
+        limiter.withdraw(tokens(999_000), DAY_10 + 5).unwrap();
+        assert_eq!(limiter.remaining(DAY_10 + 6), tokens(1_000));
+        assert_eq!(
+            limiter.withdraw(tokens(1_001), DAY_10 + 6),
+            Err(GameError::DailyLimitExceeded)
+        );
+        assert_eq!(
+            limiter.remaining(DAY_10 + SECONDS_PER_DAY),
+            tokens(1_000_000)
+        );
+
+        limiter
+            .withdraw(tokens(1_001), DAY_10 + SECONDS_PER_DAY)
+            .unwrap();
+        assert_eq!(limiter.current_day(), 11);
+        assert_eq!(limiter.todays_minted(), tokens(1_001));
+    }
+
+    #[test]
+    fn test_availability() {
+        let mut limiter = DailyMintLimiter::new(&GameConfig::default(), DAY_10);
+        limiter.record_withdrawn(tokens(600_000), DAY_10).unwrap();
+
+        assert_eq!(
+            limiter.availability(tokens(400_000), DAY_10 + 100),
+            WithdrawAvailability::Now
+        );
+        assert_eq!(
+            limiter.availability(tokens(400_001), DAY_10 + 100),
+            WithdrawAvailability::NextDay {
+                at_timestamp: DAY_10 + SECONDS_PER_DAY,
+                wait_seconds: SECONDS_PER_DAY - 100,
+            }
+        );
+        assert_eq!(
+            limiter.availability(tokens(1_000_001), DAY_10),
+            WithdrawAvailability::Never
+        );
+        assert_eq!(
+            limiter.check(TokenAmount::ZERO, DAY_10),
+            Err(GameError::NoPendingRewards)
+        );
+    }
+
+    #[test]
+    fn test_set_daily_mint_limit() {
+        let mut limiter = DailyMintLimiter::new(&GameConfig::default(), DAY_10);
+
+        assert_eq!(
+            limiter.set_daily_mint_limit(TokenAmount::ZERO),
+            Err(GameError::InvalidLimit)
+        );
+        assert_eq!(
+            limiter.set_daily_mint_limit(tokens(1_000_001)),
+            Err(GameError::LimitTooHigh)
+        );
+        limiter.set_daily_mint_limit(tokens(500)).unwrap();
+        assert_eq!(limiter.remaining(DAY_10), tokens(500));
+    }
+
+    #[test]
+    fn test_contention_near_limit() {
+        let mut limiter = DailyMintLimiter::new(&GameConfig::default(), DAY_10);
+        limiter.record_withdrawn(tokens(990_000), DAY_10).unwrap();
+
+        let requests: Vec<WithdrawRequest> = [6_000, 5_000, 4_000, 3_000]
+            .iter()
+            .map(|amount| WithdrawRequest {
+                player: Address::ZERO,
+                amount: tokens(*amount),
+                timestamp: DAY_10 + 60,
+            })
+            .collect();
+        let report = limiter.simulate(&requests).unwrap();
+
+        let errors: Vec<Option<GameError>> = report.outcomes.iter().map(|o| o.error).collect();
+        assert_eq!(
+            errors,
+            vec![
+                None,
+                Some(GameError::DailyLimitExceeded),
+                None,
+                Some(GameError::DailyLimitExceeded)
+            ]
+        );
+        assert_eq!(report.minted, tokens(10_000));
+        assert_eq!(report.reverted, 2);
+        assert_eq!(report.reverted_amount, tokens(8_000));
+        // The simulation does not touch the limiter itself
+        assert_eq!(limiter.todays_minted(), tokens(990_000));
+    }
+
+    #[test]
+    fn test_huge_amounts_revert_instead_of_panicking() {
+        let huge = TokenAmount::from(primitive_types::U256::MAX);
+        let mut limiter = DailyMintLimiter::new(&GameConfig::default(), DAY_10);
+        limiter.record_withdrawn(tokens(1), DAY_10).unwrap();
+
+        assert_eq!(
+            limiter.record_withdrawn(huge, DAY_10),
+            Err(GameError::ArithmeticOverflow)
+        );
+        assert_eq!(limiter.todays_minted(), tokens(1));
+
+        let requests: Vec<WithdrawRequest> = (0..2)
+            .map(|_| WithdrawRequest {
+                player: Address::ZERO,
+                amount: huge,
+                timestamp: DAY_10,
+            })
+            .collect();
+        assert_eq!(
+            limiter.simulate(&requests),
+            Err(GameError::ArithmeticOverflow)
+        );
+    }
+}
@@ -0,0 +1,431 @@
+//! Seeded Monte Carlo sessions through the contract tap loop.
+//!
+//! Each session is a run of `taps` taps split into `MAX_TAPS_PER_CALL`
+//! batches and replayed with `execute_taps`, carrying `tapsSinceCritical`
+//! from batch to batch. The batch seeds come from a Keccak stream rooted at
+//! the run seed, so a report is reproducible from its parameters alone.
+//! `SourceKind::Fast` swaps the Keccak stream for `FastRng` in large runs.
+
+use alloc::{string::String, vec, vec::Vec};
+
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+use crate::amount::TokenAmount;
+use crate::config::GameConfig;
+use crate::error::GameError;
+use crate::gems::GemType;
+use crate::power::{base_tap_reward, TapPath};
+use crate::random::{FastRng, KeccakChain, RandomSource, SourceKind};
+use crate::taps::execute_taps_with;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct MonteCarloParams {
+    pub sessions: u32,
+    /// Taps per session
+    pub taps: u32,
+    /// Root of the Keccak stream
+    pub seed: U256,
+    /// Pity state every session starts from
+    pub taps_since_critical: u64,
+    pub total_power: u128,
+    pub path: TapPath,
+    pub histogram_bins: u32,
+    pub source: SourceKind,
+}
+
+impl Default for MonteCarloParams {
+    fn default() -> Self {
+        MonteCarloParams {
+            sessions: 1_000,
+            taps: 100,
+            seed: U256::zero(),
+            taps_since_critical: 0,
+            total_power: 0,
+            path: TapPath::TapMine,
+            histogram_bins: 20,
+            source: SourceKind::Keccak,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GemCounts {
+    pub ruby: u64,
+    pub sapphire: u64,
+    pub diamond: u64,
+}
+
+impl GemCounts {
+    pub fn get(&self, gem: GemType) -> u64 {
+        match gem {
+            GemType::Ruby => self.ruby,
+            GemType::Sapphire => self.sapphire,
+            GemType::Diamond => self.diamond,
+        }
+    }
+
+    pub fn add(&mut self, gem: GemType, count: u64) {
+        match gem {
+            GemType::Ruby => self.ruby += count,
+            GemType::Sapphire => self.sapphire += count,
+            GemType::Diamond => self.diamond += count,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.ruby + self.sapphire + self.diamond
+    }
+}
+
+/// What happened in one session
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionResult {
+    pub total_reward: TokenAmount,
+    pub criticals: u64,
+    pub gems: GemCounts,
+    pub max_tap_reward: TokenAmount,
+    /// Crits that landed while the pity bonus was active
+    pub pity_resets: u64,
+    /// Longest run of taps without a crit
+    pub longest_dry_streak: u64,
+}
+
+/// Nearest-rank percentiles of a sample
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Percentiles {
+    pub min: f64,
+    pub p5: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl Percentiles {
+    pub fn from_samples(samples: &[f64]) -> Self {
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let Some(max) = sorted.last().copied() else {
+            return Percentiles::default();
+        };
+        let rank = |percent: f64| {
+            let index = libm::ceil(percent / 100.0 * sorted.len() as f64) as usize;
+            sorted[index.saturating_sub(1)]
+        };
+        Percentiles {
+            min: sorted[0],
+            p5: rank(5.0),
+            p50: rank(50.0),
+            p95: rank(95.0),
+            p99: rank(99.0),
+            max,
+        }
+    }
+}
+
+/// Equal-width bins over `[min, max]`, the last bin closed
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Histogram {
+    pub min: f64,
+    pub max: f64,
+    pub bin_width: f64,
+    pub counts: Vec<u64>,
+}
+
+impl Histogram {
+    pub fn from_samples(samples: &[f64], bins: u32) -> Self {
+        let min = samples.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = samples.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        if samples.is_empty() || bins == 0 {
+            return Histogram::default();
+        }
+
+        let bins = if max > min { bins as usize } else { 1 };
+        let bin_width = (max - min) / bins as f64;
+        let mut counts = vec![0; bins];
+        for sample in samples {
+            let bin = if bin_width > 0.0 {
+                ((sample - min) / bin_width) as usize
+            } else {
+                0
+            };
+            counts[bin.min(bins - 1)] += 1;
+        }
+        Histogram {
+            min,
+            max,
+            bin_width,
+            counts,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MonteCarloReport {
+    pub params: MonteCarloParams,
+    /// Mean session reward in MINE
+    pub mean_reward: f64,
+    /// Session rewards in MINE
+    pub reward: Percentiles,
+    pub criticals: Percentiles,
+    pub pity_resets: Percentiles,
+    pub longest_dry_streak: Percentiles,
+    /// Gems found over all sessions
+    pub gems: GemCounts,
+    pub max_tap_reward: TokenAmount,
+    /// Longest dry streak of any session
+    pub worst_dry_streak: u64,
+    pub reward_histogram: Histogram,
+}
+
+impl MonteCarloReport {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+}
+
+/// Word `index` of the stream rooted at `seed`:
+/// `keccak256(abi.encodePacked(uint256 seed, uint64 index))`
+pub fn stream_seed(seed: U256, index: u64) -> U256 {
+    let mut packed = [0u8; 40];
+    seed.write_as_big_endian(&mut packed[..32]);
+    packed[32..].copy_from_slice(&index.to_be_bytes());
+    U256::from_big_endian(&Keccak256::digest(packed))
+}
+
+/// Play one session with the stream rooted at `seed`
+pub fn play_session(
+    config: &GameConfig,
+    params: &MonteCarloParams,
+    seed: U256,
+) -> Result<SessionResult, GameError> {
+    match params.source {
+        SourceKind::Keccak => play_session_with(config, params, |batch| {
+            KeccakChain::new(stream_seed(seed, batch))
+        }),
+        SourceKind::Fast => {
+            let mut rng = FastRng::new(seed.low_u64());
+            play_session_with(config, params, |_| FastRng::new(rng.next_u64()))
+        }
+    }
+}
+
+/// Play one session, drawing each batch from `source_for(batch)`
+pub fn play_session_with<R: RandomSource>(
+    config: &GameConfig,
+    params: &MonteCarloParams,
+    mut source_for: impl FnMut(u64) -> R,
+) -> Result<SessionResult, GameError> {
+    let base = base_tap_reward(config, params.total_power, params.path)?;
+    let per_call = config.max_taps_per_call.max(1) as u32;
+    let mut result = SessionResult::default();
+    let mut taps_since_critical = params.taps_since_critical;
+    let mut streak = 0;
+    let mut remaining = params.taps;
+    let mut batch = 0;
+
+    while remaining > 0 {
+        let taps = remaining.min(per_call);
+        let execution = execute_taps_with(
+            config,
+            &mut source_for(batch),
+            taps as u16,
+            taps_since_critical,
+            base,
+        )?;
+
+        for outcome in &execution.outcomes {
+            if outcome.is_critical {
+                result.criticals += 1;
+                if outcome.critical_chance > config.critical_base_chance {
+                    result.pity_resets += 1;
+                }
+                streak = 0;
+            } else {
+                streak += 1;
+                result.longest_dry_streak = result.longest_dry_streak.max(streak);
+            }
+            if let Some(gem) = outcome.gem {
+                result.gems.add(gem, 1);
+            }
+            result.max_tap_reward = result.max_tap_reward.max(outcome.reward);
+        }
+
+        result.total_reward = result
+            .total_reward
+            .checked_add(execution.total_reward)
+            .ok_or(GameError::ArithmeticOverflow)?;
+        taps_since_critical = execution.taps_since_critical;
+        remaining -= taps;
+        batch += 1;
+    }
+
+    Ok(result)
+}
+
+/// Play `params.sessions` sessions and summarize them
+pub fn simulate_sessions(
+    config: &GameConfig,
+    params: &MonteCarloParams,
+) -> Result<MonteCarloReport, GameError> {
+    let results: Vec<SessionResult> = (0..params.sessions as u64)
+        .map(|session| play_session(config, params, stream_seed(params.seed, session)))
+        .collect::<Result<_, _>>()?;
+
+    let sample =
+        |value: fn(&SessionResult) -> f64| -> Vec<f64> { results.iter().map(value).collect() };
+    let rewards = sample(|r| r.total_reward.to_tokens_f64());
+
+    let mut gems = GemCounts::default();
+    for result in &results {
+        for gem in GemType::ALL {
+            gems.add(gem, result.gems.get(gem));
+        }
+    }
+
+    Ok(MonteCarloReport {
+        params: params.clone(),
+        mean_reward: rewards.iter().sum::<f64>() / rewards.len().max(1) as f64,
+        reward: Percentiles::from_samples(&rewards),
+        criticals: Percentiles::from_samples(&sample(|r| r.criticals as f64)),
+        pity_resets: Percentiles::from_samples(&sample(|r| r.pity_resets as f64)),
+        longest_dry_streak: Percentiles::from_samples(&sample(|r| r.longest_dry_streak as f64)),
+        gems,
+        max_tap_reward: results
+            .iter()
+            .map(|r| r.max_tap_reward)
+            .max()
+            .unwrap_or_default(),
+        worst_dry_streak: results
+            .iter()
+            .map(|r| r.longest_dry_streak)
+            .max()
+            .unwrap_or_default(),
+        reward_histogram: Histogram::from_samples(&rewards, params.histogram_bins),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pity::PityChain;
+    use crate::taps::execute_taps;
+
+    fn params(sessions: u32, taps: u32) -> MonteCarloParams {
+        MonteCarloParams {
+            sessions,
+            taps,
+            seed: U256::from(42),
+            total_power: 9,
+            ..MonteCarloParams::default()
+        }
+    }
+
+    #[test]
+    fn test_reports_are_reproducible() {
+        let config = GameConfig::default();
+        let a = simulate_sessions(&config, &params(20, 45)).unwrap();
+        let b = simulate_sessions(&config, &params(20, 45)).unwrap();
+        assert_eq!(a, b);
+
+        let other = MonteCarloParams {
+            seed: U256::from(43),
+            ..params(20, 45)
+        };
+        assert_ne!(a.reward, simulate_sessions(&config, &other).unwrap().reward);
+
+        let json = a.to_json().unwrap();
+        let back: MonteCarloReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, a);
+    }
+
+    #[test]
+    fn test_session_uses_contract_batches() {
+        let config = GameConfig::default();
+        let params = params(1, 45);
+        let seed = stream_seed(params.seed, 0);
+        let result = play_session(&config, &params, seed).unwrap();
+
+        // 20 + 20 + 5 taps, each batch on its own stream word
+        let base = base_tap_reward(&config, 9, TapPath::TapMine).unwrap();
+        let first = execute_taps(&config, stream_seed(seed, 0), 20, 0, base).unwrap();
+        let second = execute_taps(
+            &config,
+            stream_seed(seed, 1),
+            20,
+            first.taps_since_critical,
+            base,
+        )
+        .unwrap();
+        let third = execute_taps(
+            &config,
+            stream_seed(seed, 2),
+            5,
+            second.taps_since_critical,
+            base,
+        )
+        .unwrap();
+        assert_eq!(
+            result.total_reward,
+            first.total_reward + second.total_reward + third.total_reward
+        );
+        assert_eq!(
+            result.criticals,
+            first.critical_hits + second.critical_hits + third.critical_hits
+        );
+    }
+
+    #[test]
+    fn test_mean_matches_markov_forecast() {
+        let config = GameConfig::default();
+        let report = simulate_sessions(&config, &params(2_000, 50)).unwrap();
+        let forecast = PityChain::new(config.clone())
+            .forecast(
+                0,
+                50,
+                base_tap_reward(&config, 9, TapPath::TapMine).unwrap(),
+            )
+            .unwrap();
+
+        let error = (report.mean_reward - forecast.expected_reward).abs();
+        assert!(error / forecast.expected_reward < 0.05);
+        assert!(report.reward.p5 <= report.reward.p50);
+        assert!(report.reward.p95 <= report.reward.p99);
+        assert_eq!(report.reward_histogram.counts.iter().sum::<u64>(), 2_000);
+        assert!(report.worst_dry_streak >= report.longest_dry_streak.max as u64);
+
+        let fast = simulate_sessions(
+            &config,
+            &MonteCarloParams {
+                source: SourceKind::Fast,
+                ..params(2_000, 50)
+            },
+        )
+        .unwrap();
+        let error = (fast.mean_reward - forecast.expected_reward).abs();
+        assert!(error / forecast.expected_reward < 0.05);
+        assert_ne!(fast.reward, report.reward);
+    }
+
+    #[test]
+    fn test_percentiles_nearest_rank() {
+        let samples: Vec<f64> = (1..=100).rev().map(f64::from).collect();
+        let percentiles = Percentiles::from_samples(&samples);
+
+        assert_eq!(
+            (percentiles.min, percentiles.p5, percentiles.p50),
+            (1.0, 5.0, 50.0)
+        );
+        assert_eq!(
+            (percentiles.p95, percentiles.p99, percentiles.max),
+            (95.0, 99.0, 100.0)
+        );
+        assert_eq!(Percentiles::from_samples(&[]), Percentiles::default());
+
+        let histogram = Histogram::from_samples(&[3.0, 3.0], 10);
+        assert_eq!(histogram.counts, vec![2]);
+    }
+}
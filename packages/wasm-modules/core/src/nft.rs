@@ -0,0 +1,62 @@
+//! Miner rarities of `MinerNFT`.
+//!
+//! The contract stores the rarity as a `uint8` enum and gives each one a
+//! fixed starting power in `mintMiner`; upgrades add to the power only.
+
+use serde::{Deserialize, Serialize};
+
+/// `MinerNFT.Rarity`, named like `Rarity` in `packages/shared`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Rarity {
+    Common,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+impl Rarity {
+    pub const ALL: [Rarity; 4] = [
+        Rarity::Common,
+        Rarity::Rare,
+        Rarity::Epic,
+        Rarity::Legendary,
+    ];
+
+    /// Value of the variant in the Solidity enum
+    pub fn id(&self) -> u8 {
+        *self as u8
+    }
+
+    pub fn from_id(id: u8) -> Option<Rarity> {
+        Rarity::ALL.get(id as usize).copied()
+    }
+
+    /// Power `mintMiner` gives a miner of this rarity
+    pub fn base_power(&self) -> u128 {
+        match self {
+            Rarity::Common => 1,
+            Rarity::Rare => 3,
+            Rarity::Epic => 7,
+            Rarity::Legendary => 15,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rarity_ids() {
+        for rarity in Rarity::ALL {
+            assert_eq!(Rarity::from_id(rarity.id()), Some(rarity));
+        }
+        assert_eq!(Rarity::Legendary.id(), 3);
+        assert_eq!(Rarity::from_id(4), None);
+        assert_eq!(
+            serde_json::to_string(&Rarity::Legendary).unwrap(),
+            "\"LEGENDARY\""
+        );
+    }
+}
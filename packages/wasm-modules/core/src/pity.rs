@@ -0,0 +1,292 @@
+//! Markov chain over `tapsSinceCritical`.
+//!
+//! The critical chance only changes up to `pity_saturation`, so the states
+//! are `0..=pity_saturation`, the last one standing for every longer streak.
+//! A tap moves a player from state `k` to 0 with the critical chance of `k`,
+//! and to `k + 1` (capped) otherwise. Everything here is exact up to float
+//! rounding, no sampling involved.
+
+use alloc::{vec, vec::Vec};
+
+use serde::{Deserialize, Serialize};
+
+use crate::amount::TokenAmount;
+use crate::config::GameConfig;
+use crate::distribution::tap_reward_distribution;
+use crate::error::GameError;
+use crate::taps::{critical_chance, pity_saturation};
+
+/// When the next critical hit lands
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TapsUntilCritical {
+    /// `probabilities[i]` is the chance that the next crit is tap `i + 1`
+    pub probabilities: Vec<f64>,
+    /// Chance of no crit within `probabilities.len()` taps
+    pub beyond: f64,
+    /// Mean number of taps up to and including the crit, `None` if it never comes
+    pub expected_taps: Option<f64>,
+}
+
+/// Expected outcome of a session of `taps` taps
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SessionForecast {
+    pub taps: u32,
+    pub expected_criticals: f64,
+    /// Expected reward in MINE
+    pub expected_reward: f64,
+    /// Distribution of the pity state after the session
+    pub final_states: Vec<f64>,
+}
+
+#[derive(Clone)]
+pub struct PityChain {
+    config: GameConfig,
+    /// Critical chance of each state, as a probability
+    chances: Vec<f64>,
+}
+
+impl PityChain {
+    pub fn new(config: GameConfig) -> Self {
+        let chances = (0..=pity_saturation(&config))
+            .map(|state| critical_chance(&config, state).min(100) as f64 / 100.0)
+            .collect();
+        PityChain { config, chances }
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    /// Number of states, `pity_saturation + 1`
+    pub fn states(&self) -> usize {
+        self.chances.len()
+    }
+
+    /// State for an on-chain `tapsSinceCritical`
+    pub fn state_of(&self, taps_since_critical: u64) -> usize {
+        taps_since_critical.min(self.states() as u64 - 1) as usize
+    }
+
+    fn next_state(&self, state: usize) -> usize {
+        (state + 1).min(self.states() - 1)
+    }
+
+    /// All the mass on the state of `taps_since_critical`
+    pub fn initial(&self, taps_since_critical: u64) -> Vec<f64> {
+        let mut distribution = vec![0.0; self.states()];
+        distribution[self.state_of(taps_since_critical)] = 1.0;
+        distribution
+    }
+
+    /// Advance a state distribution by one tap, returns the chance of a crit
+    pub fn step(&self, distribution: &mut [f64]) -> f64 {
+        let mut next = vec![0.0; self.states()];
+        for (state, mass) in distribution.iter().enumerate() {
+            let critical = mass * self.chances[state];
+            next[0] += critical;
+            next[self.next_state(state)] += mass - critical;
+        }
+        distribution.copy_from_slice(&next);
+        distribution[0]
+    }
+
+    /// Distribution of the number of taps until the next crit, over `max_taps`
+    pub fn taps_until_critical(
+        &self,
+        taps_since_critical: u64,
+        max_taps: u32,
+    ) -> TapsUntilCritical {
+        let start = self.state_of(taps_since_critical);
+        let mut probabilities = Vec::with_capacity(max_taps as usize);
+        let mut survival = 1.0;
+        let mut state = start;
+        for _ in 0..max_taps {
+            probabilities.push(survival * self.chances[state]);
+            survival *= 1.0 - self.chances[state];
+            state = self.next_state(state);
+        }
+
+        // Exact mean: walk up to the last state, then the wait is geometric
+        let last = self.states() - 1;
+        let mut expected = 0.0;
+        let mut survival_to = 1.0;
+        for (taps, state) in (start..last).enumerate() {
+            expected += (taps + 1) as f64 * survival_to * self.chances[state];
+            survival_to *= 1.0 - self.chances[state];
+        }
+        let expected_taps = if survival_to == 0.0 {
+            Some(expected)
+        } else if self.chances[last] == 0.0 {
+            None
+        } else {
+            let walked = last.saturating_sub(start) as f64;
+            Some(expected + survival_to * (walked + 1.0 / self.chances[last]))
+        };
+
+        TapsUntilCritical {
+            probabilities,
+            beyond: survival,
+            expected_taps,
+        }
+    }
+
+    /// `counts[n]` is the chance of exactly `n` crits over the next `taps` taps
+    pub fn critical_counts(&self, taps_since_critical: u64, taps: u32) -> Vec<f64> {
+        let taps = taps as usize;
+        // mass[state][crits so far], rolled into `next` one tap at a time
+        let mut mass = vec![vec![0.0; taps + 1]; self.states()];
+        let mut next = mass.clone();
+        mass[self.state_of(taps_since_critical)][0] = 1.0;
+
+        for tap in 0..taps {
+            // After `tap + 1` taps only counts up to `tap + 1` can be non-zero
+            for counts in next.iter_mut() {
+                counts[..=tap + 1].fill(0.0);
+            }
+            for (state, counts) in mass.iter().enumerate() {
+                let chance = self.chances[state];
+                let up = self.next_state(state);
+                for (crits, value) in counts.iter().enumerate().take(tap + 1) {
+                    next[0][crits + 1] += value * chance;
+                    next[up][crits] += value * (1.0 - chance);
+                }
+            }
+            core::mem::swap(&mut mass, &mut next);
+        }
+
+        (0..=taps)
+            .map(|crits| mass.iter().map(|counts| counts[crits]).sum())
+            .collect()
+    }
+
+    /// Expected crits and MINE over `taps` taps at `base_tap_reward`
+    ///
+    /// Fails with `ArithmeticOverflow` when a tap reward overflows `uint256`.
+    pub fn forecast(
+        &self,
+        taps_since_critical: u64,
+        taps: u32,
+        base_tap_reward: TokenAmount,
+    ) -> Result<SessionForecast, GameError> {
+        let means = (0..self.states())
+            .map(|state| {
+                tap_reward_distribution(&self.config, state as u64, base_tap_reward)
+                    .map(|distribution| distribution.mean)
+            })
+            .collect::<Result<Vec<f64>, GameError>>()?;
+
+        let mut distribution = self.initial(taps_since_critical);
+        let mut expected_criticals = 0.0;
+        let mut expected_reward = 0.0;
+        for _ in 0..taps {
+            expected_reward += distribution
+                .iter()
+                .zip(&means)
+                .map(|(mass, mean)| mass * mean)
+                .sum::<f64>();
+            expected_criticals += self.step(&mut distribution);
+        }
+
+        Ok(SessionForecast {
+            taps,
+            expected_criticals,
+            expected_reward,
+            final_states: distribution,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flat() -> GameConfig {
+        GameConfig {
+            pity_increment: 0,
+            ..GameConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_flat_chance_is_geometric_and_binomial() {
+        let chain = PityChain::new(flat());
+        assert_eq!(chain.states(), 1);
+
+        let wait = chain.taps_until_critical(123, 3);
+        let expected = [0.1, 0.09, 0.081];
+        for (p, e) in wait.probabilities.iter().zip(expected) {
+            assert!((p - e).abs() < 1e-12);
+        }
+        assert!((wait.beyond - 0.729).abs() < 1e-12);
+        assert!((wait.expected_taps.unwrap() - 10.0).abs() < 1e-9);
+
+        let counts = chain.critical_counts(0, 3);
+        for (p, e) in counts.iter().zip([0.729, 0.243, 0.027, 0.001]) {
+            assert!((p - e).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_pity_shortens_the_wait() {
+        let chain = PityChain::new(GameConfig::default());
+        assert_eq!(chain.states(), 76);
+
+        // 60% from saturation on
+        let wait = chain.taps_until_critical(200, 2);
+        assert!((wait.probabilities[0] - 0.6).abs() < 1e-12);
+        assert!((wait.expected_taps.unwrap() - 1.0 / 0.6).abs() < 1e-9);
+
+        // The closed-form mean agrees with a long truncated sum
+        let wait = chain.taps_until_critical(0, 2_000);
+        let mean: f64 = wait
+            .probabilities
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (i + 1) as f64 * p)
+            .sum();
+        assert!(wait.beyond < 1e-12);
+        assert!((wait.expected_taps.unwrap() - mean).abs() < 1e-9);
+        assert!(wait.expected_taps.unwrap() < 10.0);
+
+        let never = PityChain::new(GameConfig {
+            critical_base_chance: 0,
+            max_pity_bonus: 0,
+            ..GameConfig::default()
+        });
+        assert_eq!(never.taps_until_critical(0, 10).expected_taps, None);
+    }
+
+    #[test]
+    fn test_critical_counts_match_forecast() {
+        let chain = PityChain::new(GameConfig::default());
+        let counts = chain.critical_counts(40, 120);
+        let forecast = chain
+            .forecast(40, 120, TokenAmount::from_tokens(10))
+            .unwrap();
+
+        assert!((counts.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        let mean: f64 = counts.iter().enumerate().map(|(n, p)| n as f64 * p).sum();
+        assert!((mean - forecast.expected_criticals).abs() < 1e-9);
+        assert!((forecast.final_states.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_forecast_rewards() {
+        let config = GameConfig::default();
+        let base = TokenAmount::from_tokens(10);
+        let chain = PityChain::new(config.clone());
+
+        let one = chain.forecast(0, 1, base).unwrap();
+        assert!((one.expected_reward - 25.75).abs() < 1e-9);
+        assert!((one.expected_criticals - 0.1).abs() < 1e-12);
+
+        let flat_chain = PityChain::new(flat());
+        let session = flat_chain.forecast(0, 50, base).unwrap();
+        assert!((session.expected_reward - 50.0 * 25.75).abs() < 1e-6);
+
+        // Deep in a dry streak the next taps are worth more
+        let dry = chain.forecast(70, 10, base).unwrap();
+        let fresh = chain.forecast(0, 10, base).unwrap();
+        assert!(dry.expected_reward > fresh.expected_reward);
+    }
+}
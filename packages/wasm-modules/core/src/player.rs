@@ -0,0 +1,116 @@
+//! Mirror of `MinerGame.PlayerData`.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::address::Address;
+use crate::amount::TokenAmount;
+use crate::error::GameError;
+use crate::taps::TapExecution;
+
+/// Player state as returned by `getPlayerData`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct PlayerData {
+    pub level: u32,
+    pub total_power: u128,
+    pub pending_rewards: TokenAmount,
+    pub total_mined: TokenAmount,
+    pub total_taps: u64,
+    pub critical_hits: u64,
+    pub last_tap_block: u64,
+    pub taps_since_critical: u64,
+    pub registered_miners: Vec<u64>,
+}
+
+/// `PlayerData` in the shape of the frontend's `PlayerStats`, without `miners`
+///
+/// Rarity is not on chain and power changes with upgrades, so the miner list
+/// has to come from the NFT contract.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerStatsSummary {
+    pub address: Address,
+    pub total_power: u128,
+    pub pending_rewards: TokenAmount,
+    pub total_mined: TokenAmount,
+    pub total_taps: u64,
+    pub critical_hits: u64,
+    pub last_tap_block: u64,
+}
+
+impl PlayerData {
+    /// Player updates of `_executeTaps` once the tap loop is done
+    ///
+    /// Fails with `ArithmeticOverflow` where the contract's checked
+    /// `pendingRewards` addition reverts, leaving the player untouched. The
+    /// counters are bumped in `unchecked` blocks on chain and wrap here.
+    pub fn record_taps(&mut self, taps: u16, execution: &TapExecution) -> Result<(), GameError> {
+        self.pending_rewards = self
+            .pending_rewards
+            .checked_add(execution.total_reward)
+            .ok_or(GameError::ArithmeticOverflow)?;
+        self.total_taps = self.total_taps.wrapping_add(taps as u64);
+        self.critical_hits = self.critical_hits.wrapping_add(execution.critical_hits);
+        self.taps_since_critical = execution.taps_since_critical;
+        Ok(())
+    }
+
+    pub fn stats(&self, address: Address) -> PlayerStatsSummary {
+        PlayerStatsSummary {
+            address,
+            total_power: self.total_power,
+            pending_rewards: self.pending_rewards,
+            total_mined: self.total_mined,
+            total_taps: self.total_taps,
+            critical_hits: self.critical_hits,
+            last_tap_block: self.last_tap_block,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn execution(total_reward: u64, critical_hits: u64) -> TapExecution {
+        TapExecution {
+            outcomes: Vec::new(),
+            total_reward: TokenAmount::from(total_reward),
+            has_critical: critical_hits > 0,
+            critical_hits,
+            taps_since_critical: 0,
+            final_seed: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_record_taps_wraps_counters() {
+        let mut player = PlayerData {
+            total_taps: u64::MAX,
+            critical_hits: u64::MAX,
+            ..PlayerData::default()
+        };
+
+        assert_eq!(player.record_taps(2, &execution(30, 1)), Ok(()));
+        assert_eq!(player.total_taps, 1);
+        assert_eq!(player.critical_hits, 0);
+        assert_eq!(player.pending_rewards, TokenAmount::from(30));
+    }
+
+    #[test]
+    fn test_record_taps_reverts_on_pending_overflow() {
+        let mut player = PlayerData {
+            pending_rewards: TokenAmount::from(primitive_types::U256::MAX),
+            ..PlayerData::default()
+        };
+        let before = player.clone();
+
+        assert_eq!(
+            player.record_taps(1, &execution(1, 0)),
+            Err(GameError::ArithmeticOverflow)
+        );
+        assert_eq!(player, before);
+    }
+}
@@ -0,0 +1,247 @@
+//! Power and per-tap base reward, mirroring `_calculateAndCachePower` and
+//! the head of `_executeTaps`.
+//!
+//! The contract sums the power of every registered miner the player still
+//! owns, multiplies the sum by the *player* level + 1 and reverts if the
+//! result does not fit in `uint128`.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+
+use crate::address::Address;
+use crate::amount::TokenAmount;
+use crate::config::GameConfig;
+use crate::error::GameError;
+use crate::player::PlayerData;
+
+/// On-chain view of a miner NFT, as returned by `ownerOf` and `getMiner`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MinerInfo {
+    pub token_id: u64,
+    pub owner: Address,
+    pub power: u128,
+}
+
+/// Which entry point the taps go through
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TapPath {
+    /// `commitTap` + `revealTap`, paid with the commit-reveal bonus
+    CommitReveal,
+    /// `tapMine`, paid the plain base reward
+    TapMine,
+}
+
+/// How the power of a player was put together
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PowerBreakdown {
+    /// Registered miners still owned by the player
+    pub counted_miners: Vec<u64>,
+    /// Registered miners that were transferred or no longer exist
+    pub skipped_miners: Vec<u64>,
+    pub miner_power: U256,
+    pub level: u32,
+    pub total_power: u128,
+    pub base_tap_reward: TokenAmount,
+}
+
+/// `minerPower * (level + 1)`, with the contract's `uint128` overflow check
+pub fn apply_level(miner_power: U256, level: u32) -> Result<u128, GameError> {
+    let total = miner_power
+        .checked_mul(U256::from(level) + U256::one())
+        .ok_or(GameError::TotalPowerOverflow)?;
+    if total > U256::from(u128::MAX) {
+        return Err(GameError::TotalPowerOverflow);
+    }
+    Ok(total.as_u128())
+}
+
+/// Total power for a list of miner powers the player is known to own
+pub fn total_power(miner_powers: &[u128], level: u32) -> Result<u128, GameError> {
+    let sum = miner_powers
+        .iter()
+        .fold(U256::zero(), |acc, power| acc + U256::from(*power));
+    apply_level(sum, level)
+}
+
+/// `BASE_REWARD * (totalPower + 1)`, plus the bonus on the commit-reveal path
+///
+/// Fails with `ArithmeticOverflow` on a config whose reward does not fit.
+pub fn base_tap_reward(
+    config: &GameConfig,
+    total_power: u128,
+    path: TapPath,
+) -> Result<TokenAmount, GameError> {
+    let reward = config
+        .base_reward
+        .checked_mul(U256::from(total_power) + U256::one());
+    match path {
+        TapPath::CommitReveal => reward
+            .and_then(|reward| reward.checked_mul(config.commit_reveal_bonus_percent))
+            .map(|reward| TokenAmount::from_wei(reward.wei() / 100)),
+        TapPath::TapMine => reward,
+    }
+    .ok_or(GameError::ArithmeticOverflow)
+}
+
+/// Mirror of `_calculateAndCachePower` followed by the base reward of `_executeTaps`
+///
+/// `miners` is the NFT state; registered ids missing from it behave like a
+/// reverting `ownerOf` and are skipped.
+pub fn calculate_power(
+    config: &GameConfig,
+    player_address: &Address,
+    player: &PlayerData,
+    miners: &[MinerInfo],
+    path: TapPath,
+) -> Result<PowerBreakdown, GameError> {
+    let miners: BTreeMap<u64, &MinerInfo> = miners.iter().map(|m| (m.token_id, m)).collect();
+
+    let mut counted_miners = Vec::new();
+    let mut skipped_miners = Vec::new();
+    let mut miner_power = U256::zero();
+
+    for token_id in &player.registered_miners {
+        match miners.get(token_id) {
+            Some(miner) if miner.owner == *player_address => {
+                miner_power += U256::from(miner.power);
+                counted_miners.push(*token_id);
+            }
+            _ => skipped_miners.push(*token_id),
+        }
+    }
+
+    let total_power = apply_level(miner_power, player.level)?;
+
+    Ok(PowerBreakdown {
+        counted_miners,
+        skipped_miners,
+        miner_power,
+        level: player.level,
+        total_power,
+        base_tap_reward: base_tap_reward(config, total_power, path)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player_address() -> Address {
+        "0x70997970C51812dc3A010C7d01b50e0d17dc79C8"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_total_power_uses_player_level() {
+        // (1 + 3 + 7) * (2 + 1)
+        assert_eq!(total_power(&[1, 3, 7], 2), Ok(33));
+        assert_eq!(total_power(&[], 5), Ok(0));
+    }
+
+    #[test]
+    fn test_total_power_overflow() {
+        assert_eq!(total_power(&[u128::MAX], 0), Ok(u128::MAX));
+        assert_eq!(
+            total_power(&[u128::MAX], 1),
+            Err(GameError::TotalPowerOverflow)
+        );
+        assert_eq!(
+            total_power(&[u128::MAX, 1], 0),
+            Err(GameError::TotalPowerOverflow)
+        );
+    }
+
+    #[test]
+    fn test_base_tap_reward_paths() {
+        let config = GameConfig::default();
+
+        assert_eq!(
+            base_tap_reward(&config, 0, TapPath::TapMine),
+            Ok(TokenAmount::from_tokens(10))
+        );
+        assert_eq!(
+            base_tap_reward(&config, 4, TapPath::TapMine),
+            Ok(TokenAmount::from_tokens(50))
+        );
+        assert_eq!(
+            base_tap_reward(&config, 4, TapPath::CommitReveal),
+            Ok(TokenAmount::from_tokens(55))
+        );
+
+        let config = GameConfig {
+            base_reward: TokenAmount::from(U256::MAX / 2),
+            ..config
+        };
+        assert_eq!(
+            base_tap_reward(&config, 0, TapPath::TapMine),
+            Ok(config.base_reward)
+        );
+        assert_eq!(
+            base_tap_reward(&config, 2, TapPath::TapMine),
+            Err(GameError::ArithmeticOverflow)
+        );
+        assert_eq!(
+            base_tap_reward(&config, 0, TapPath::CommitReveal),
+            Err(GameError::ArithmeticOverflow)
+        );
+    }
+
+    #[test]
+    fn test_calculate_power_filters_ownership() {
+        let other: Address = "0x3C44CdDdB6a900fa2b585dd299e03d12FA4293BC"
+            .parse()
+            .unwrap();
+        let miners = vec![
+            MinerInfo {
+                token_id: 1,
+                owner: player_address(),
+                power: 15,
+            },
+            MinerInfo {
+                token_id: 2,
+                owner: other,
+                power: 7,
+            },
+            MinerInfo {
+                token_id: 3,
+                owner: player_address(),
+                power: 3,
+            },
+        ];
+        let player = PlayerData {
+            level: 1,
+            registered_miners: vec![1, 2, 3, 4],
+            ..PlayerData::default()
+        };
+
+        let breakdown = calculate_power(
+            &GameConfig::default(),
+            &player_address(),
+            &player,
+            &miners,
+            TapPath::CommitReveal,
+        )
+        .unwrap();
+
+        assert_eq!(breakdown.counted_miners, vec![1, 3]);
+        assert_eq!(breakdown.skipped_miners, vec![2, 4]);
+        assert_eq!(breakdown.miner_power, U256::from(18));
+        assert_eq!(breakdown.total_power, 36);
+        // 10 MINE * 37 * 110%
+        assert_eq!(breakdown.base_tap_reward, TokenAmount::from_tokens(407));
+    }
+
+    #[test]
+    fn test_total_power() {
+        let powers = vec![100, 200, 300];
+        let total = total_power(&powers, 2).unwrap();
+
+        // (100 + 200 + 300) * (2 + 1) = 1800
+        assert_eq!(total, 1800);
+    }
+}
@@ -0,0 +1,262 @@
+//! Random words behind the tap mechanics.
+//!
+//! Every roll of a tap is taken from one 256-bit word: `word % 100` for the
+//! critical check, the multiplier and the gem check, `(word >> 8) % 100` for
+//! the gem kind. A `RandomSource` hands out those words, so the same
+//! mechanics run on the contract's seed chain, the legacy local prediction,
+//! a fast PRNG or a scripted sequence.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+use crate::taps::next_seed;
+
+pub trait RandomSource {
+    /// Word for tap `index` of the current call
+    fn next_word(&mut self, index: u16) -> U256;
+}
+
+/// Which source a simulation draws from
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceKind {
+    /// Contract seed chain, exact
+    #[default]
+    Keccak,
+    /// `FastRng`, same distribution at a fraction of the cost
+    Fast,
+}
+
+/// The contract's chain: each tap re-hashes the previous seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeccakChain {
+    seed: U256,
+}
+
+impl KeccakChain {
+    pub fn new(initial_seed: U256) -> Self {
+        KeccakChain { seed: initial_seed }
+    }
+
+    /// Last word handed out, the initial seed before any
+    pub fn seed(&self) -> U256 {
+        self.seed
+    }
+}
+
+impl RandomSource for KeccakChain {
+    fn next_word(&mut self, index: u16) -> U256 {
+        self.seed = next_seed(self.seed, index);
+        self.seed
+    }
+}
+
+/// Local guess used by `predict_tap_reward`: the first 8 bytes,
+/// little-endian, of `keccak256(address || block || nonce)`
+///
+/// Each word moves on to the next nonce.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegacyPrediction {
+    user_address: String,
+    block_number: u64,
+    nonce: u32,
+}
+
+impl LegacyPrediction {
+    pub fn new(user_address: &str, block_number: u64, nonce: u32) -> Self {
+        LegacyPrediction {
+            user_address: user_address.to_string(),
+            block_number,
+            nonce,
+        }
+    }
+}
+
+impl RandomSource for LegacyPrediction {
+    fn next_word(&mut self, _index: u16) -> U256 {
+        let seed = generate_seed(&self.user_address, self.block_number, self.nonce);
+        self.nonce = self.nonce.wrapping_add(1);
+        U256::from(calculate_random_value(&seed))
+    }
+}
+
+/// Generate deterministic seed from user address and block data
+fn generate_seed(user_address: &str, block_number: u64, nonce: u32) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(user_address.as_bytes());
+    hasher.update(block_number.to_be_bytes());
+    hasher.update(nonce.to_be_bytes());
+
+    let result = hasher.finalize();
+    let mut seed = [0u8; 32];
+    seed.copy_from_slice(&result);
+    seed
+}
+
+/// Calculate pseudo-random value from seed
+fn calculate_random_value(seed: &[u8; 32]) -> u64 {
+    let mut value = 0u64;
+    for (i, &byte) in seed.iter().take(8).enumerate() {
+        value |= (byte as u64) << (i * 8);
+    }
+    value
+}
+
+/// xoshiro256** seeded through SplitMix64, not cryptographic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FastRng {
+    state: [u64; 4],
+}
+
+impl FastRng {
+    pub fn new(seed: u64) -> Self {
+        let mut splitmix = seed;
+        let mut next = || {
+            splitmix = splitmix.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = splitmix;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        };
+        FastRng {
+            state: [next(), next(), next(), next()],
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+}
+
+impl RandomSource for FastRng {
+    fn next_word(&mut self, _index: u16) -> U256 {
+        U256([
+            self.next_u64(),
+            self.next_u64(),
+            self.next_u64(),
+            self.next_u64(),
+        ])
+    }
+}
+
+/// Fixed words, repeated once exhausted; zero if empty
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScriptedSource {
+    words: Vec<U256>,
+    position: usize,
+}
+
+impl ScriptedSource {
+    pub fn new(words: Vec<U256>) -> Self {
+        ScriptedSource { words, position: 0 }
+    }
+
+    /// Word whose main roll is `roll` and gem roll is `gem_roll`, both below 100
+    pub fn word(roll: u64, gem_roll: u64) -> U256 {
+        let high = U256::from(gem_roll) << 8;
+        let offset = (roll + 100 - (high % U256::from(100u8)).low_u64()) % 100;
+        high + U256::from(offset)
+    }
+
+    /// One word per `(roll, gem_roll)` pair
+    pub fn from_rolls(rolls: &[(u64, u64)]) -> Self {
+        ScriptedSource::new(
+            rolls
+                .iter()
+                .map(|(roll, gem_roll)| ScriptedSource::word(*roll, *gem_roll))
+                .collect(),
+        )
+    }
+}
+
+impl RandomSource for ScriptedSource {
+    fn next_word(&mut self, _index: u16) -> U256 {
+        if self.words.is_empty() {
+            return U256::zero();
+        }
+        let word = self.words[self.position % self.words.len()];
+        self.position += 1;
+        word
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::TokenAmount;
+    use crate::config::GameConfig;
+    use crate::gems::GemType;
+    use crate::taps::{execute_taps, execute_taps_with, roll};
+
+    #[test]
+    fn test_seed_generation() {
+        let seed1 = generate_seed("0x123", 1000, 1);
+        let seed2 = generate_seed("0x123", 1000, 1);
+        let seed3 = generate_seed("0x123", 1001, 1);
+
+        assert_eq!(seed1, seed2);
+        assert_ne!(seed1, seed3);
+    }
+
+    #[test]
+    fn test_keccak_chain_matches_execute_taps() {
+        let config = GameConfig::default();
+        let seed = U256::from(0x1234);
+        let base = TokenAmount::from_tokens(10);
+
+        let mut chain = KeccakChain::new(seed);
+        let generic = execute_taps_with(&config, &mut chain, 20, 3, base).unwrap();
+        assert_eq!(generic, execute_taps(&config, seed, 20, 3, base).unwrap());
+        assert_eq!(chain.seed(), generic.final_seed);
+    }
+
+    #[test]
+    fn test_scripted_rolls_force_outcomes() {
+        let config = GameConfig {
+            critical_base_chance: 100,
+            ..GameConfig::default()
+        };
+        let word = ScriptedSource::word(97, 95);
+        assert_eq!(roll(word), 97);
+        assert_eq!(roll(word >> 8), 95);
+
+        // x50 crit, then a x2 crit with a Diamond
+        let mut source = ScriptedSource::from_rolls(&[(97, 0), (4, 99)]);
+        let execution =
+            execute_taps_with(&config, &mut source, 2, 0, TokenAmount::from_tokens(10)).unwrap();
+        assert_eq!(execution.outcomes[0].multiplier, 50);
+        assert_eq!(execution.outcomes[0].gem, None);
+        assert_eq!(execution.outcomes[1].multiplier, 2);
+        assert_eq!(execution.outcomes[1].gem, Some(GemType::Diamond));
+        assert_eq!(execution.total_reward, TokenAmount::from_tokens(2_520));
+    }
+
+    #[test]
+    fn test_fast_rng_rolls() {
+        let mut a = FastRng::new(7);
+        let mut b = FastRng::new(7);
+        assert_eq!(a.next_word(0), b.next_word(0));
+        assert_ne!(a.next_word(0), FastRng::new(8).next_word(0));
+
+        let mut counts = [0u32; 100];
+        for i in 0..100_000 {
+            counts[roll(a.next_word(i as u16)) as usize] += 1;
+        }
+        assert!(counts.iter().all(|count| (800..1_200).contains(count)));
+    }
+}
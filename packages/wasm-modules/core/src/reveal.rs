@@ -0,0 +1,133 @@
+//! Outcome of `MinerGame.revealTap`, computed off-chain.
+//!
+//! Once the block after `CommitmentMade` is mined, the reveal seed
+//! `keccak256(abi.encodePacked(blockhash(commitBlock + 1), secret, nonce, sender))`
+//! is fixed, so the whole reveal can be replayed before paying gas for it.
+
+use primitive_types::{H256, U256};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+use crate::address::Address;
+use crate::config::GameConfig;
+use crate::error::GameError;
+use crate::player::PlayerData;
+use crate::power::{base_tap_reward, TapPath};
+use crate::taps::{execute_taps, TapExecution};
+
+/// Result of a reveal and the player state right after it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RevealOutcome {
+    pub seed: U256,
+    pub execution: TapExecution,
+    pub player: PlayerData,
+}
+
+/// `keccak256(abi.encodePacked(bytes32 blockHash, uint256 secret, uint256 nonce, address sender))`
+pub fn reveal_seed(block_hash: &H256, secret: U256, nonce: U256, player: &Address) -> U256 {
+    let mut packed = [0u8; 32 + 32 + 32 + 20];
+    packed[..32].copy_from_slice(block_hash.as_bytes());
+    secret.write_as_big_endian(&mut packed[32..64]);
+    nonce.write_as_big_endian(&mut packed[64..96]);
+    packed[96..].copy_from_slice(player.as_bytes());
+    U256::from_big_endian(&Keccak256::digest(packed))
+}
+
+/// Replay `revealTap` for `player_address` with the given commitment opening
+///
+/// `block_hash` is the hash of block `commitBlock + 1` and `player.total_power`
+/// must be the power the contract recomputes at reveal time (see
+/// [`crate::power::calculate_power`]). The returned player keeps
+/// `last_tap_block` untouched since the reveal block is not known yet.
+/// Fails like `revealTap` on a tap count outside `1..=MAX_TAPS_PER_CALL`.
+pub fn compute_reveal(
+    config: &GameConfig,
+    block_hash: &H256,
+    player_address: &Address,
+    secret: U256,
+    nonce: U256,
+    taps: u16,
+    player: &PlayerData,
+) -> Result<RevealOutcome, GameError> {
+    config.check_tap_count(taps)?;
+    let seed = reveal_seed(block_hash, secret, nonce, player_address);
+    let base_reward = base_tap_reward(config, player.total_power, TapPath::CommitReveal)?;
+    let execution = execute_taps(config, seed, taps, player.taps_since_critical, base_reward)?;
+
+    let mut player = player.clone();
+    player.record_taps(taps, &execution)?;
+
+    Ok(RevealOutcome {
+        seed,
+        execution,
+        player,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::TokenAmount;
+
+    fn player_address() -> Address {
+        "0x70997970C51812dc3A010C7d01b50e0d17dc79C8"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_reveal_seed_layout() {
+        let block_hash = H256([0x11; 32]);
+        let mut packed = Vec::new();
+        packed.extend_from_slice(&[0x11; 32]);
+        packed.extend_from_slice(&U256::from(7).to_big_endian());
+        packed.extend_from_slice(&U256::from(9).to_big_endian());
+        packed.extend_from_slice(player_address().as_bytes());
+
+        assert_eq!(
+            reveal_seed(&block_hash, U256::from(7), U256::from(9), &player_address()),
+            U256::from_big_endian(&Keccak256::digest(&packed))
+        );
+    }
+
+    #[test]
+    fn test_compute_reveal_updates_player() {
+        let player = PlayerData {
+            total_power: 4,
+            taps_since_critical: 60,
+            pending_rewards: TokenAmount::from_tokens(1),
+            ..PlayerData::default()
+        };
+        let outcome = compute_reveal(
+            &GameConfig::default(),
+            &H256([0x22; 32]),
+            &player_address(),
+            U256::from(1),
+            U256::from(2),
+            20,
+            &player,
+        )
+        .unwrap();
+
+        assert_eq!(outcome.execution.outcomes.len(), 20);
+        assert_eq!(outcome.execution.outcomes[0].critical_chance, 30);
+        assert_eq!(
+            outcome.player.pending_rewards,
+            TokenAmount::from_tokens(1) + outcome.execution.total_reward
+        );
+        assert_eq!(outcome.player.total_taps, 20);
+        assert_eq!(
+            outcome.player.critical_hits,
+            outcome.execution.critical_hits
+        );
+        assert_eq!(
+            outcome.player.taps_since_critical,
+            outcome.execution.taps_since_critical
+        );
+
+        // Non-critical taps pay exactly BASE_REWARD * 5 * 110%
+        for tap in outcome.execution.outcomes.iter().filter(|t| !t.is_critical) {
+            assert_eq!(tap.reward, TokenAmount::from_tokens(55));
+        }
+    }
+}
@@ -0,0 +1,475 @@
+//! Local mirror of one player's `MinerGame` state.
+//!
+//! `PlayerSimulator` applies the same state transitions and checks as the
+//! contract, so the optimistic UI can run the whole game loop locally and
+//! reconcile with the chain later.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use primitive_types::{H256, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::address::Address;
+use crate::amount::TokenAmount;
+use crate::config::GameConfig;
+use crate::error::GameError;
+use crate::limiter::DailyMintLimiter;
+use crate::player::PlayerData;
+use crate::power::{calculate_power, MinerInfo, TapPath};
+use crate::reveal::reveal_seed;
+use crate::taps::{execute_taps, TapExecution};
+use crate::tracker::{CommitStatus, CommitTracker, Commitment};
+use crate::upgrade::upgrade_cost;
+
+/// Everything the simulator tracks, as saved by `export_state`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct SimulatorState {
+    pub address: Address,
+    pub player: PlayerData,
+    /// Known miner NFTs, used for ownership checks and power
+    pub miners: Vec<MinerInfo>,
+    /// MINE balance of the wallet, spent by `upgrade`
+    pub balance: TokenAmount,
+    /// Mirror of `blockTaps[player]`
+    pub block_taps: BTreeMap<u64, u64>,
+    /// Mirror of `commitments[player]`
+    pub commitment: Option<Commitment>,
+    /// Global daily mint counters, started by the first `withdraw` if unknown
+    pub mint_limiter: Option<DailyMintLimiter>,
+}
+
+pub struct PlayerSimulator {
+    config: GameConfig,
+    state: SimulatorState,
+}
+
+impl PlayerSimulator {
+    pub fn new(config: GameConfig, address: Address) -> Self {
+        PlayerSimulator {
+            config,
+            state: SimulatorState {
+                address,
+                ..SimulatorState::default()
+            },
+        }
+    }
+
+    pub fn from_state(config: GameConfig, state: SimulatorState) -> Self {
+        PlayerSimulator { config, state }
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn state(&self) -> &SimulatorState {
+        &self.state
+    }
+
+    /// Replace the whole state, keeping the config
+    pub fn set_state(&mut self, state: SimulatorState) {
+        self.state = state;
+    }
+
+    pub fn player_data(&self) -> &PlayerData {
+        &self.state.player
+    }
+
+    /// Add or replace what is known about a miner NFT
+    pub fn set_miner(&mut self, miner: MinerInfo) {
+        match self
+            .state
+            .miners
+            .iter_mut()
+            .find(|m| m.token_id == miner.token_id)
+        {
+            Some(existing) => *existing = miner,
+            None => self.state.miners.push(miner),
+        }
+    }
+
+    pub fn set_balance(&mut self, balance: TokenAmount) {
+        self.state.balance = balance;
+    }
+
+    /// `tapMine` with a known seed
+    pub fn tap(
+        &mut self,
+        seed: U256,
+        taps: u16,
+        block_number: u64,
+    ) -> Result<TapExecution, GameError> {
+        self.config.check_tap_count(taps)?;
+        self.execute(seed, taps, TapPath::TapMine, block_number)
+    }
+
+    fn commit_tracker(&self) -> CommitTracker {
+        CommitTracker::from_commitment(self.config.clone(), self.state.commitment)
+    }
+
+    /// Where the commitment stands at `block_number`
+    pub fn commit_status(&self, block_number: u64) -> CommitStatus {
+        self.commit_tracker().status(block_number)
+    }
+
+    /// `commitTap` of `hash` for `taps` taps, mined in `block_number`
+    pub fn commit(&mut self, hash: H256, taps: u16, block_number: u64) -> Result<(), GameError> {
+        let mut tracker = self.commit_tracker();
+        tracker.commit(hash, taps, block_number)?;
+        self.state.commitment = tracker.commitment().copied();
+        Ok(())
+    }
+
+    /// `revealTap` of the pending commitment, mined in `block_number`
+    ///
+    /// `block_hash` is the hash of block `commitBlock + 1`. The opening and
+    /// the block are checked like the contract does before any tap runs.
+    pub fn reveal(
+        &mut self,
+        block_hash: &H256,
+        secret: U256,
+        nonce: U256,
+        block_number: u64,
+    ) -> Result<TapExecution, GameError> {
+        let mut tracker = self.commit_tracker();
+        let taps = tracker.reveal(&self.state.address, secret, nonce, block_number)?;
+        let seed = reveal_seed(block_hash, secret, nonce, &self.state.address);
+        let execution = self.execute(seed, taps, TapPath::CommitReveal, block_number)?;
+
+        self.state.commitment = tracker.commitment().copied();
+        Ok(execution)
+    }
+
+    /// `withdraw` at `timestamp`, moving pending rewards to the wallet balance
+    pub fn withdraw(&mut self, timestamp: u64) -> Result<TokenAmount, GameError> {
+        let amount = self.state.player.pending_rewards;
+        let mut limiter = self
+            .state
+            .mint_limiter
+            .clone()
+            .unwrap_or_else(|| DailyMintLimiter::new(&self.config, timestamp));
+        limiter.withdraw(amount, timestamp)?;
+
+        let total_mined = self.state.player.total_mined.checked_add(amount);
+        let balance = self.state.balance.checked_add(amount);
+        let (Some(total_mined), Some(balance)) = (total_mined, balance) else {
+            return Err(GameError::ArithmeticOverflow);
+        };
+
+        self.state.player.pending_rewards = TokenAmount::ZERO;
+        self.state.player.total_mined = total_mined;
+        self.state.balance = balance;
+        self.state.mint_limiter = Some(limiter);
+        Ok(amount)
+    }
+
+    /// `upgrade`, burning `(level + 1) * UPGRADE_COST_MULTIPLIER` from the balance
+    pub fn upgrade(&mut self) -> Result<TokenAmount, GameError> {
+        let player = &mut self.state.player;
+        if player.level >= self.config.max_level {
+            return Err(GameError::MaxLevelReached);
+        }
+
+        let cost = upgrade_cost(&self.config, player.level)?;
+        self.state.balance = self
+            .state
+            .balance
+            .checked_sub(cost)
+            .ok_or(GameError::InsufficientBalance)?;
+        player.level += 1;
+        Ok(cost)
+    }
+
+    pub fn register_miner(&mut self, token_id: u64) -> Result<(), GameError> {
+        // `ownerOf` reverts on a token that was never minted
+        let miner = self
+            .state
+            .miners
+            .iter()
+            .find(|m| m.token_id == token_id)
+            .ok_or(GameError::NonexistentToken)?;
+        if miner.owner != self.state.address {
+            return Err(GameError::NotTheOwner);
+        }
+
+        let registered = &mut self.state.player.registered_miners;
+        if registered.len() >= self.config.max_registered_miners {
+            return Err(GameError::MaxMinersReached);
+        }
+        if registered.contains(&token_id) {
+            return Err(GameError::AlreadyRegistered);
+        }
+
+        registered.push(token_id);
+        Ok(())
+    }
+
+    /// `unregisterMiner`, which moves the last entry into the freed slot
+    pub fn unregister_miner(&mut self, token_id: u64) -> Result<(), GameError> {
+        let registered = &mut self.state.player.registered_miners;
+        match registered.iter().position(|id| *id == token_id) {
+            Some(index) => {
+                registered.swap_remove(index);
+                Ok(())
+            }
+            None => Err(GameError::MinerNotRegistered),
+        }
+    }
+
+    /// Body of `_executeTaps`
+    fn execute(
+        &mut self,
+        seed: U256,
+        taps: u16,
+        path: TapPath,
+        block_number: u64,
+    ) -> Result<TapExecution, GameError> {
+        let block_taps = self
+            .state
+            .block_taps
+            .get(&block_number)
+            .copied()
+            .unwrap_or(0);
+        let block_taps = block_taps
+            .checked_add(taps as u64)
+            .ok_or(GameError::ArithmeticOverflow)?;
+        if block_taps > self.config.max_taps_per_block {
+            return Err(GameError::BlockLimitExceeded);
+        }
+
+        let power = calculate_power(
+            &self.config,
+            &self.state.address,
+            &self.state.player,
+            &self.state.miners,
+            path,
+        )?;
+        let execution = execute_taps(
+            &self.config,
+            seed,
+            taps,
+            self.state.player.taps_since_critical,
+            power.base_tap_reward,
+        )?;
+
+        // Nothing changes until the last check passed, like a revert
+        let player = &mut self.state.player;
+        player.record_taps(taps, &execution)?;
+        player.total_power = power.total_power;
+        player.last_tap_block = block_number;
+
+        // Only the current block matters for the limit, older entries can go
+        self.state
+            .block_taps
+            .retain(|block, _| *block >= block_number);
+        self.state.block_taps.insert(block_number, block_taps);
+
+        Ok(execution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commit::commitment_hash;
+    use crate::taps::next_seed;
+    use crate::tracker::CommitState;
+
+    fn simulator() -> PlayerSimulator {
+        let address: Address = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8"
+            .parse()
+            .unwrap();
+        let mut simulator = PlayerSimulator::new(GameConfig::default(), address);
+        simulator.set_miner(MinerInfo {
+            token_id: 7,
+            owner: address,
+            power: 3,
+        });
+        simulator
+    }
+
+    #[test]
+    fn test_tap_updates_player() {
+        let mut sim = simulator();
+        sim.register_miner(7).unwrap();
+
+        let execution = sim.tap(U256::from(42), 20, 100).unwrap();
+        let player = sim.player_data();
+
+        assert_eq!(player.total_power, 3);
+        assert_eq!(player.pending_rewards, execution.total_reward);
+        assert_eq!(player.total_taps, 20);
+        assert_eq!(player.critical_hits, execution.critical_hits);
+        assert_eq!(player.taps_since_critical, execution.taps_since_critical);
+        assert_eq!(player.last_tap_block, 100);
+    }
+
+    #[test]
+    fn test_tap_limits() {
+        let mut sim = simulator();
+
+        assert_eq!(sim.tap(U256::one(), 0, 1), Err(GameError::InvalidTapCount));
+        assert_eq!(sim.tap(U256::one(), 21, 1), Err(GameError::InvalidTapCount));
+        for _ in 0..5 {
+            sim.tap(U256::one(), 20, 1).unwrap();
+        }
+        assert_eq!(
+            sim.tap(U256::one(), 1, 1),
+            Err(GameError::BlockLimitExceeded)
+        );
+        assert!(sim.tap(U256::one(), 1, 2).is_ok());
+    }
+
+    #[test]
+    fn test_withdraw_and_upgrade() {
+        let mut sim = simulator();
+
+        assert_eq!(sim.withdraw(0), Err(GameError::NoPendingRewards));
+        assert_eq!(sim.upgrade(), Err(GameError::InsufficientBalance));
+
+        sim.set_balance(TokenAmount::from_tokens(300));
+        assert_eq!(sim.upgrade(), Ok(TokenAmount::from_tokens(100)));
+        assert_eq!(sim.upgrade(), Ok(TokenAmount::from_tokens(200)));
+        assert_eq!(sim.player_data().level, 2);
+        assert_eq!(sim.state().balance, TokenAmount::ZERO);
+
+        sim.tap(U256::one(), 10, 1).unwrap();
+        let pending = sim.player_data().pending_rewards;
+        assert_eq!(sim.withdraw(0), Ok(pending));
+        assert_eq!(sim.player_data().total_mined, pending);
+        assert_eq!(sim.state().balance, pending);
+    }
+
+    #[test]
+    fn test_commit_and_reveal_follow_the_contract() {
+        let mut sim = simulator();
+        let address = sim.state().address;
+        let block_hash = H256([0xab; 32]);
+        let hash = commitment_hash(&address, U256::from(1), U256::from(2), 10);
+
+        assert_eq!(
+            sim.reveal(&block_hash, U256::from(1), U256::from(2), 100),
+            Err(GameError::NoCommitment)
+        );
+        sim.commit(hash, 10, 100).unwrap();
+        assert_eq!(sim.commit(hash, 10, 101), Err(GameError::PendingCommitment));
+        assert_eq!(
+            sim.reveal(&block_hash, U256::from(1), U256::from(2), 100),
+            Err(GameError::TooEarly)
+        );
+        assert_eq!(
+            sim.reveal(&block_hash, U256::from(1), U256::from(2), 357),
+            Err(GameError::TooLate)
+        );
+        assert_eq!(
+            sim.reveal(&block_hash, U256::from(1), U256::from(3), 102),
+            Err(GameError::InvalidReveal)
+        );
+        assert_eq!(sim.player_data().total_taps, 0);
+
+        let execution = sim
+            .reveal(&block_hash, U256::from(1), U256::from(2), 102)
+            .unwrap();
+        let seed = reveal_seed(&block_hash, U256::from(1), U256::from(2), &address);
+        assert_eq!(execution.outcomes.len(), 10);
+        assert_eq!(execution.outcomes[0].seed, next_seed(seed, 0));
+        assert_eq!(sim.player_data().total_taps, 10);
+        assert_eq!(sim.commit_status(102).state, CommitState::Revealed);
+        assert_eq!(
+            sim.reveal(&block_hash, U256::from(1), U256::from(2), 103),
+            Err(GameError::AlreadyRevealed)
+        );
+        assert_eq!(sim.commit(hash, 10, 103), Ok(()));
+    }
+
+    #[test]
+    fn test_withdraw_applies_daily_limit() {
+        let config = GameConfig {
+            daily_mint_limit: TokenAmount::from_tokens(100),
+            ..GameConfig::default()
+        };
+        let mut sim = PlayerSimulator::from_state(config, simulator().state().clone());
+        let pending = |sim: &mut PlayerSimulator| {
+            let mut state = sim.state().clone();
+            state.player.pending_rewards = TokenAmount::from_tokens(60);
+            sim.set_state(state);
+        };
+
+        pending(&mut sim);
+        assert_eq!(sim.withdraw(10), Ok(TokenAmount::from_tokens(60)));
+        pending(&mut sim);
+        assert_eq!(sim.withdraw(1_000), Err(GameError::DailyLimitExceeded));
+        assert_eq!(
+            sim.player_data().pending_rewards,
+            TokenAmount::from_tokens(60)
+        );
+        // The next day starts from zero again
+        assert_eq!(sim.withdraw(86_400), Ok(TokenAmount::from_tokens(60)));
+        assert_eq!(sim.state().balance, TokenAmount::from_tokens(120));
+    }
+
+    #[test]
+    fn test_overflow_reverts_without_changes() {
+        let unlimited = TokenAmount::from(U256::MAX);
+        let config = GameConfig {
+            max_daily_mint: unlimited,
+            daily_mint_limit: unlimited,
+            ..GameConfig::default()
+        };
+        let mut sim = PlayerSimulator::from_state(config, simulator().state().clone());
+        let mut state = sim.state().clone();
+        state.player.pending_rewards = TokenAmount::from(U256::MAX);
+        state.balance = TokenAmount::from(1);
+        sim.set_state(state.clone());
+
+        assert_eq!(
+            sim.tap(U256::one(), 20, 1),
+            Err(GameError::ArithmeticOverflow)
+        );
+        assert_eq!(sim.withdraw(0), Err(GameError::ArithmeticOverflow));
+        assert_eq!(sim.state(), &state);
+
+        // A block count loaded from outside cannot wrap past the limit
+        let mut state = simulator().state().clone();
+        state.block_taps.insert(1, u64::MAX);
+        sim.set_state(state.clone());
+        assert_eq!(
+            sim.tap(U256::one(), 1, 1),
+            Err(GameError::ArithmeticOverflow)
+        );
+        assert_eq!(sim.state(), &state);
+    }
+
+    #[test]
+    fn test_register_and_unregister() {
+        let mut sim = simulator();
+
+        assert_eq!(sim.register_miner(8), Err(GameError::NonexistentToken));
+        let mut state = sim.state().clone();
+        state.miners.push(MinerInfo {
+            token_id: 8,
+            owner: Address::ZERO,
+            power: 1,
+        });
+        sim.set_state(state);
+        assert_eq!(sim.register_miner(8), Err(GameError::NotTheOwner));
+
+        sim.register_miner(7).unwrap();
+        assert_eq!(sim.register_miner(7), Err(GameError::AlreadyRegistered));
+        sim.unregister_miner(7).unwrap();
+        assert_eq!(sim.unregister_miner(7), Err(GameError::MinerNotRegistered));
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let mut sim = simulator();
+        sim.register_miner(7).unwrap();
+        sim.tap(U256::from(9), 5, 3).unwrap();
+
+        let json = serde_json::to_string(sim.state()).unwrap();
+        let state: SimulatorState = serde_json::from_str(&json).unwrap();
+        assert_eq!(&state, sim.state());
+    }
+}
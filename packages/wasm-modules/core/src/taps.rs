@@ -0,0 +1,525 @@
+//! Bit-exact port of the per-tap loop in `MinerGame._executeTaps`.
+//!
+//! Every tap re-hashes the running seed as
+//! `keccak256(abi.encodePacked(uint256 seed, uint16 i))` and all rolls are
+//! taken from the full 256-bit value modulo 100, exactly like the contract.
+//! `execute_taps_with` runs the same loop on any `RandomSource`.
+
+use alloc::{vec, vec::Vec};
+
+use primitive_types::{H256, U256};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+use crate::address::Address;
+use crate::amount::TokenAmount;
+use crate::config::GameConfig;
+use crate::error::GameError;
+use crate::gems::{gem_for_critical, GemType};
+use crate::random::{KeccakChain, LegacyPrediction, RandomSource};
+
+/// Outcome of a single tap inside a batch
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TapOutcome {
+    pub index: u16,
+    pub seed: U256,
+    pub critical_chance: u64,
+    pub is_critical: bool,
+    pub multiplier: u32,
+    pub gem: Option<GemType>,
+    pub gem_bonus: TokenAmount,
+    pub reward: TokenAmount,
+}
+
+/// Result of replaying a whole `_executeTaps` call
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TapExecution {
+    pub outcomes: Vec<TapOutcome>,
+    pub total_reward: TokenAmount,
+    pub has_critical: bool,
+    pub critical_hits: u64,
+    pub taps_since_critical: u64,
+    pub final_seed: U256,
+}
+
+/// A single tap in the shape of the frontend's `TapResult`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TapResult {
+    pub reward: TokenAmount,
+    pub is_critical: bool,
+    /// Only set on a critical hit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub critical_multiplier: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gem_found: Option<GemType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gem_bonus: Option<TokenAmount>,
+}
+
+/// A predicted `TapResult` and the base reward it was computed from
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RewardPrediction {
+    pub base_reward: TokenAmount,
+    pub reward: TokenAmount,
+    pub is_critical: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub critical_multiplier: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gem_found: Option<GemType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gem_bonus: Option<TokenAmount>,
+}
+
+impl From<&TapOutcome> for TapResult {
+    fn from(outcome: &TapOutcome) -> Self {
+        TapResult {
+            reward: outcome.reward,
+            is_critical: outcome.is_critical,
+            critical_multiplier: outcome.is_critical.then_some(outcome.multiplier),
+            gem_found: outcome.gem,
+            gem_bonus: outcome.gem.map(|_| outcome.gem_bonus),
+        }
+    }
+}
+
+/// Parse a seed given as hex, with or without the `0x` prefix
+pub fn parse_seed(seed: &str) -> Option<U256> {
+    let digits = seed.strip_prefix("0x").unwrap_or(seed);
+    if digits.is_empty() || digits.len() > 64 {
+        return None;
+    }
+    U256::from_str_radix(digits, 16).ok()
+}
+
+/// Advance the seed chain: `keccak256(abi.encodePacked(seed, uint16 index))`
+pub fn next_seed(seed: U256, index: u16) -> U256 {
+    let mut packed = [0u8; 34];
+    seed.write_as_big_endian(&mut packed[..32]);
+    packed[32..].copy_from_slice(&index.to_be_bytes());
+    U256::from_big_endian(&Keccak256::digest(packed))
+}
+
+/// Seed `tapMine` hands to `_executeTaps`: `keccak256(abi.encodePacked(
+/// block.timestamp, block.prevrandao, sender, blockhash(block.number - 1)))`
+pub fn tap_mine_seed(
+    timestamp: u64,
+    prev_randao: U256,
+    player: &Address,
+    parent_hash: &H256,
+) -> U256 {
+    let mut packed = [0u8; 32 + 32 + 20 + 32];
+    U256::from(timestamp).write_as_big_endian(&mut packed[..32]);
+    prev_randao.write_as_big_endian(&mut packed[32..64]);
+    packed[64..84].copy_from_slice(player.as_bytes());
+    packed[84..].copy_from_slice(parent_hash.as_bytes());
+    U256::from_big_endian(&Keccak256::digest(packed))
+}
+
+/// `seed % 100`, the roll used for both the critical and gem checks
+pub fn roll(seed: U256) -> u64 {
+    (seed % U256::from(100u8)).low_u64()
+}
+
+/// Mirror of `_calculatePityBonus`
+pub fn pity_bonus(config: &GameConfig, taps_since_critical: u64) -> u64 {
+    if taps_since_critical <= config.pity_threshold {
+        return 0;
+    }
+    ((taps_since_critical - config.pity_threshold).saturating_mul(config.pity_increment))
+        .min(config.max_pity_bonus)
+}
+
+/// Smallest `tapsSinceCritical` from which the pity bonus no longer grows
+pub fn pity_saturation(config: &GameConfig) -> u64 {
+    if config.pity_increment == 0 || config.max_pity_bonus == 0 {
+        return 0;
+    }
+    config.pity_threshold + config.max_pity_bonus.div_ceil(config.pity_increment)
+}
+
+/// Critical chance in percent for the next tap
+pub fn critical_chance(config: &GameConfig, taps_since_critical: u64) -> u64 {
+    config.critical_base_chance + pity_bonus(config, taps_since_critical)
+}
+
+/// Multiplier picked by `criticalWeights` for a roll in `0..100`
+pub fn multiplier_for_roll(config: &GameConfig, random: u64) -> u32 {
+    let mut cumulative = 0;
+
+    for (multiplier, weight) in config
+        .critical_multipliers
+        .iter()
+        .zip(config.critical_weights.iter())
+    {
+        cumulative += weight;
+        if random < cumulative {
+            return *multiplier;
+        }
+    }
+
+    config.critical_multipliers[0]
+}
+
+/// Long-run share of taps spent in each pity state `0..=pity_saturation`
+///
+/// Crits reset the state to 0, so the share of state `k` is proportional to
+/// the chance of reaching `k` taps without a crit. The last state absorbs
+/// every longer streak.
+pub fn stationary_pity_distribution(config: &GameConfig) -> Vec<f64> {
+    let cap = pity_saturation(config) as usize;
+    let mut weights = Vec::with_capacity(cap + 1);
+    let mut survival = 1.0;
+
+    for state in 0..=cap {
+        let chance = critical_chance(config, state as u64).min(100) as f64 / 100.0;
+        if state == cap {
+            if chance == 0.0 {
+                // Without any critical chance every player ends up here
+                let mut absorbed = vec![0.0; cap + 1];
+                absorbed[cap] = 1.0;
+                return absorbed;
+            }
+            weights.push(survival / chance);
+        } else {
+            weights.push(survival);
+            survival *= 1.0 - chance;
+        }
+    }
+
+    let total: f64 = weights.iter().sum();
+    weights.iter().map(|weight| weight / total).collect()
+}
+
+/// Mirror of `_selectMultiplier`
+pub fn select_multiplier(config: &GameConfig, seed: U256) -> u32 {
+    multiplier_for_roll(config, roll(seed))
+}
+
+/// Replay `taps` iterations of the contract loop starting from `initial_seed`
+pub fn execute_taps(
+    config: &GameConfig,
+    initial_seed: U256,
+    taps: u16,
+    taps_since_critical: u64,
+    base_tap_reward: TokenAmount,
+) -> Result<TapExecution, GameError> {
+    let mut chain = KeccakChain::new(initial_seed);
+    let mut execution = execute_taps_with(
+        config,
+        &mut chain,
+        taps,
+        taps_since_critical,
+        base_tap_reward,
+    )?;
+    execution.final_seed = chain.seed();
+    Ok(execution)
+}
+
+/// Run `taps` iterations of the contract loop on words from `source`
+///
+/// `final_seed` is the last word drawn, zero if none was. Fails with
+/// `ArithmeticOverflow` where the contract's checked arithmetic reverts.
+pub fn execute_taps_with<R: RandomSource>(
+    config: &GameConfig,
+    source: &mut R,
+    taps: u16,
+    taps_since_critical: u64,
+    base_tap_reward: TokenAmount,
+) -> Result<TapExecution, GameError> {
+    let mut seed = U256::zero();
+    let mut taps_since_critical = taps_since_critical;
+    let mut outcomes = Vec::with_capacity(taps as usize);
+    let mut total_reward = TokenAmount::ZERO;
+    let mut critical_hits = 0;
+
+    for i in 0..taps {
+        seed = source.next_word(i);
+
+        let chance = critical_chance(config, taps_since_critical);
+        let is_critical = roll(seed) < chance;
+
+        let (multiplier, gem) = if is_critical {
+            critical_hits += 1;
+            taps_since_critical = 0;
+            (
+                select_multiplier(config, seed),
+                gem_for_critical(config, seed),
+            )
+        } else {
+            // `unchecked` in the contract
+            taps_since_critical = taps_since_critical.wrapping_add(1);
+            (1, None)
+        };
+
+        let gem_bonus = gem
+            .map(|gem| config.gem_rewards.get(gem).bonus)
+            .unwrap_or_default();
+        let reward = base_tap_reward
+            .checked_mul(U256::from(multiplier))
+            .and_then(|reward| reward.checked_add(gem_bonus))
+            .ok_or(GameError::ArithmeticOverflow)?;
+        total_reward = total_reward
+            .checked_add(reward)
+            .ok_or(GameError::ArithmeticOverflow)?;
+
+        outcomes.push(TapOutcome {
+            index: i,
+            seed,
+            critical_chance: chance,
+            is_critical,
+            multiplier,
+            gem,
+            gem_bonus,
+            reward,
+        });
+    }
+
+    Ok(TapExecution {
+        outcomes,
+        total_reward,
+        has_critical: critical_hits > 0,
+        critical_hits,
+        taps_since_critical,
+        final_seed: seed,
+    })
+}
+
+/// Guess the next tap locally from the address, block number and nonce
+///
+/// Only the contract's seed chain is exact, see [`LegacyPrediction`].
+pub fn predict_tap_reward(
+    config: &GameConfig,
+    user_address: &str,
+    base_reward: TokenAmount,
+    taps_since_critical: u64,
+    block_number: u64,
+    nonce: u32,
+) -> Result<RewardPrediction, GameError> {
+    let mut source = LegacyPrediction::new(user_address, block_number, nonce);
+    let execution = execute_taps_with(config, &mut source, 1, taps_since_critical, base_reward)?;
+    let tap = TapResult::from(&execution.outcomes[0]);
+
+    Ok(RewardPrediction {
+        base_reward,
+        reward: tap.reward,
+        is_critical: tap.is_critical,
+        critical_multiplier: tap.critical_multiplier,
+        gem_found: tap.gem_found,
+        gem_bonus: tap.gem_bonus,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_seed_matches_packed_keccak() {
+        // abi.encodePacked(uint256(1), uint16(2)) = 31 zero bytes, 0x01, 0x00, 0x02
+        let mut packed = [0u8; 34];
+        packed[31] = 1;
+        packed[33] = 2;
+        let expected = U256::from_big_endian(&Keccak256::digest(packed));
+
+        assert_eq!(next_seed(U256::one(), 2), expected);
+    }
+
+    #[test]
+    fn test_tap_mine_seed_layout() {
+        let player = Address([0x22; 20]);
+        let mut packed = Vec::new();
+        packed.extend_from_slice(&U256::from(1_700_000_000u64).to_big_endian());
+        packed.extend_from_slice(&U256::from(5).to_big_endian());
+        packed.extend_from_slice(&[0x22; 20]);
+        packed.extend_from_slice(&[0x33; 32]);
+
+        assert_eq!(
+            tap_mine_seed(1_700_000_000, U256::from(5), &player, &H256([0x33; 32])),
+            U256::from_big_endian(&Keccak256::digest(&packed))
+        );
+    }
+
+    #[test]
+    fn test_pity_bonus() {
+        let config = GameConfig::default();
+        assert_eq!(pity_bonus(&config, 0), 0);
+        assert_eq!(pity_bonus(&config, 50), 0);
+        assert_eq!(pity_bonus(&config, 51), 2);
+        assert_eq!(pity_bonus(&config, 70), 40);
+        assert_eq!(pity_bonus(&config, 75), 50);
+        assert_eq!(pity_bonus(&config, u64::MAX), 50);
+    }
+
+    #[test]
+    fn test_pity_saturation() {
+        let config = GameConfig::default();
+        let cap = pity_saturation(&config);
+
+        assert_eq!(cap, 75);
+        assert_eq!(pity_bonus(&config, cap), config.max_pity_bonus);
+        assert!(pity_bonus(&config, cap - 1) < config.max_pity_bonus);
+    }
+
+    #[test]
+    fn test_custom_pity_config() {
+        let config = GameConfig {
+            pity_threshold: 10,
+            pity_increment: 5,
+            max_pity_bonus: 20,
+            ..GameConfig::default()
+        };
+        assert_eq!(critical_chance(&config, 10), 10);
+        assert_eq!(critical_chance(&config, 12), 20);
+        assert_eq!(critical_chance(&config, 100), 30);
+    }
+
+    #[test]
+    fn test_select_multiplier_buckets() {
+        let config = GameConfig::default();
+        assert_eq!(select_multiplier(&config, U256::from(0)), 2);
+        assert_eq!(select_multiplier(&config, U256::from(59)), 2);
+        assert_eq!(select_multiplier(&config, U256::from(60)), 5);
+        assert_eq!(select_multiplier(&config, U256::from(85)), 10);
+        assert_eq!(select_multiplier(&config, U256::from(95)), 50);
+        assert_eq!(select_multiplier(&config, U256::from(199)), 50);
+
+        let config = GameConfig {
+            critical_weights: [0, 0, 0, 100],
+            ..GameConfig::default()
+        };
+        assert_eq!(select_multiplier(&config, U256::from(0)), 50);
+    }
+
+    #[test]
+    fn test_execute_taps_chains_seed() {
+        let initial = parse_seed("0x1234").unwrap();
+        let config = GameConfig::default();
+        let result = execute_taps(&config, initial, 20, 0, TokenAmount::from(10)).unwrap();
+
+        let mut seed = initial;
+        for outcome in &result.outcomes {
+            seed = next_seed(seed, outcome.index);
+            assert_eq!(outcome.seed, seed);
+            assert_eq!(outcome.is_critical, roll(seed) < outcome.critical_chance);
+            assert_eq!(outcome.gem.is_some(), outcome.is_critical && roll(seed) < 5);
+        }
+        assert_eq!(result.final_seed, seed);
+
+        let sum: TokenAmount = result.outcomes.iter().map(|o| o.reward).sum();
+        assert_eq!(result.total_reward, sum);
+        assert_eq!(
+            result.critical_hits as usize,
+            result.outcomes.iter().filter(|o| o.is_critical).count()
+        );
+    }
+
+    #[test]
+    fn test_execute_taps_reverts_on_overflow() {
+        let config = GameConfig::default();
+        let seed = parse_seed("0x1234").unwrap();
+
+        assert_eq!(
+            execute_taps(&config, seed, 20, 0, TokenAmount::from(U256::MAX)),
+            Err(GameError::ArithmeticOverflow)
+        );
+    }
+
+    #[test]
+    fn test_pity_counter_wraps_like_the_contract() {
+        // Without any critical chance the counter only grows, unchecked on chain
+        let config = GameConfig {
+            critical_base_chance: 0,
+            max_pity_bonus: 0,
+            ..GameConfig::default()
+        };
+        let seed = parse_seed("0x1234").unwrap();
+
+        let execution = execute_taps(&config, seed, 2, u64::MAX, TokenAmount::from(10)).unwrap();
+        assert!(!execution.has_critical);
+        assert_eq!(execution.taps_since_critical, 1);
+        assert_eq!(execution.total_reward, TokenAmount::from(20));
+    }
+
+    #[test]
+    fn test_parse_seed() {
+        assert_eq!(parse_seed("0xff"), Some(U256::from(255)));
+        assert_eq!(parse_seed("ff"), Some(U256::from(255)));
+        assert_eq!(parse_seed("0x"), None);
+        assert_eq!(parse_seed("0xzz"), None);
+        assert_eq!(parse_seed(&"f".repeat(65)), None);
+    }
+
+    #[test]
+    fn test_stationary_pity_distribution() {
+        let config = GameConfig::default();
+        let distribution = stationary_pity_distribution(&config);
+
+        assert_eq!(distribution.len(), 76);
+        assert!((distribution.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        // Every crit lands in state 0, so its share is the long-run crit rate
+        let crit_rate: f64 = distribution
+            .iter()
+            .enumerate()
+            .map(|(state, share)| share * critical_chance(&config, state as u64) as f64 / 100.0)
+            .sum();
+        assert!((distribution[0] - crit_rate).abs() < 1e-12);
+
+        let flat = GameConfig {
+            pity_increment: 0,
+            ..GameConfig::default()
+        };
+        assert_eq!(stationary_pity_distribution(&flat), vec![1.0]);
+    }
+
+    #[test]
+    fn test_tap_result_shape() {
+        let outcome = TapOutcome {
+            index: 0,
+            seed: U256::zero(),
+            critical_chance: 10,
+            is_critical: true,
+            multiplier: 2,
+            gem: Some(GemType::Ruby),
+            gem_bonus: TokenAmount::from_tokens(100),
+            reward: TokenAmount::from_tokens(120),
+        };
+        let json = serde_json::to_value(TapResult::from(&outcome)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "reward": "120000000000000000000",
+                "isCritical": true,
+                "criticalMultiplier": 2,
+                "gemFound": "RUBY",
+                "gemBonus": "100000000000000000000"
+            })
+        );
+
+        // A plain tap has no multiplier, gem or bonus at all
+        let plain = TapOutcome {
+            is_critical: false,
+            multiplier: 1,
+            gem: None,
+            gem_bonus: TokenAmount::ZERO,
+            reward: TokenAmount::from_tokens(10),
+            ..outcome
+        };
+        let json = serde_json::to_value(TapResult::from(&plain)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"reward": "10000000000000000000", "isCritical": false})
+        );
+    }
+
+    #[test]
+    fn test_critical_multipliers() {
+        // Test multiplier distribution
+        let config = GameConfig::default();
+        for value in 0..100 {
+            let mult = multiplier_for_roll(&config, value);
+            assert!(mult == 2 || mult == 5 || mult == 10 || mult == 50);
+        }
+    }
+}
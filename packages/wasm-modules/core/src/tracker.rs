@@ -0,0 +1,351 @@
+//! Lifecycle of the single pending commitment `MinerGame` keeps per player.
+//!
+//! `revealTap` needs `blockhash(commitBlock + COMMIT_REVEAL_BLOCKS)`, which is
+//! zero while that block is the current one, and must land no later than
+//! `commitBlock + MAX_REVEAL_DELAY`. A commitment that misses the window is
+//! never cleared: `commitTap` keeps reverting with "Pending commitment", so
+//! only `tapMine` is left to that player. All block numbers here are the
+//! block the transaction is expected to be mined in.
+
+use alloc::{vec, vec::Vec};
+
+use primitive_types::{H256, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::address::Address;
+use crate::commit::verify_commitment;
+use crate::config::GameConfig;
+use crate::error::GameError;
+
+/// Mirror of `MinerGame.CommitData`, as returned by `getCommitment`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Commitment {
+    pub hash: H256,
+    pub block_number: u64,
+    pub taps: u16,
+    pub revealed: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CommitState {
+    /// No commitment was ever made
+    Idle,
+    /// Committed, the reveal block hash is not available yet
+    Committed,
+    /// Inside the reveal window
+    Revealable,
+    /// The reveal window closed, the commitment blocks `commitTap` for good
+    Expired,
+    /// Revealed, a new commitment can be made
+    Revealed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CommitAction {
+    Commit,
+    Reveal,
+    TapMine,
+}
+
+/// Where a commitment stands at a given block
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CommitStatus {
+    pub state: CommitState,
+    pub commitment: Option<Commitment>,
+    /// First block a reveal can be mined in
+    pub reveal_from: Option<u64>,
+    /// Last block a reveal can be mined in
+    pub reveal_until: Option<u64>,
+    /// Blocks to wait before the reveal window opens, 0 once it is open
+    pub blocks_until_revealable: u64,
+    /// Blocks left in the reveal window after the given one, 0 outside it
+    pub blocks_remaining: u64,
+    pub allowed_actions: Vec<CommitAction>,
+}
+
+pub struct CommitTracker {
+    config: GameConfig,
+    commitment: Option<Commitment>,
+}
+
+impl CommitTracker {
+    pub fn new(config: GameConfig) -> Self {
+        CommitTracker {
+            config,
+            commitment: None,
+        }
+    }
+
+    /// Start from the on-chain commitment, `None` if the player never committed
+    pub fn from_commitment(config: GameConfig, commitment: Option<Commitment>) -> Self {
+        let mut tracker = CommitTracker {
+            config,
+            commitment: None,
+        };
+        tracker.set_commitment(commitment);
+        tracker
+    }
+
+    pub fn commitment(&self) -> Option<&Commitment> {
+        self.commitment.as_ref()
+    }
+
+    /// Replace the tracked commitment, e.g. after reading it back from chain
+    pub fn set_commitment(&mut self, commitment: Option<Commitment>) {
+        self.commitment = commitment.filter(|c| c.block_number != 0);
+    }
+
+    /// Block whose hash seeds the reveal
+    pub fn seed_block(&self) -> Option<u64> {
+        self.commitment.map(|c| {
+            c.block_number
+                .saturating_add(self.config.commit_reveal_blocks)
+        })
+    }
+
+    /// Inclusive range of blocks a reveal can be mined in
+    ///
+    /// `blockhash` is zero for the current block and for blocks more than
+    /// 256 back, which narrows the contract's `Too early` / `Too late` bounds.
+    /// The bounds saturate, block numbers from outside cannot overflow them.
+    pub fn reveal_window(&self) -> Option<(u64, u64)> {
+        let commitment = self.commitment?;
+        let seed_block = commitment
+            .block_number
+            .saturating_add(self.config.commit_reveal_blocks);
+        let until = commitment
+            .block_number
+            .saturating_add(self.config.max_reveal_delay)
+            .min(seed_block.saturating_add(256));
+        Some((seed_block.saturating_add(1), until))
+    }
+
+    pub fn state(&self, block_number: u64) -> CommitState {
+        let Some(commitment) = self.commitment else {
+            return CommitState::Idle;
+        };
+        if commitment.revealed {
+            return CommitState::Revealed;
+        }
+
+        match self.reveal_window() {
+            Some((from, _)) if block_number < from => CommitState::Committed,
+            Some((_, until)) if block_number <= until => CommitState::Revealable,
+            _ => CommitState::Expired,
+        }
+    }
+
+    pub fn allowed_actions(&self, block_number: u64) -> Vec<CommitAction> {
+        match self.state(block_number) {
+            CommitState::Idle | CommitState::Revealed => {
+                vec![CommitAction::Commit, CommitAction::TapMine]
+            }
+            CommitState::Revealable => vec![CommitAction::Reveal, CommitAction::TapMine],
+            CommitState::Committed | CommitState::Expired => vec![CommitAction::TapMine],
+        }
+    }
+
+    pub fn status(&self, block_number: u64) -> CommitStatus {
+        let state = self.state(block_number);
+        let window = self.reveal_window();
+        let (blocks_until_revealable, blocks_remaining) = match (state, window) {
+            (CommitState::Committed, Some((from, _))) => (from - block_number, 0),
+            (CommitState::Revealable, Some((_, until))) => (0, until - block_number),
+            _ => (0, 0),
+        };
+
+        CommitStatus {
+            state,
+            commitment: self.commitment,
+            reveal_from: window.map(|(from, _)| from),
+            reveal_until: window.map(|(_, until)| until),
+            blocks_until_revealable,
+            blocks_remaining,
+            allowed_actions: self.allowed_actions(block_number),
+        }
+    }
+
+    /// `commitTap`, rejected while any unrevealed commitment exists
+    pub fn commit(&mut self, hash: H256, taps: u16, block_number: u64) -> Result<(), GameError> {
+        self.config.check_tap_count(taps)?;
+        if matches!(self.commitment, Some(c) if !c.revealed) {
+            return Err(GameError::PendingCommitment);
+        }
+
+        self.commitment = Some(Commitment {
+            hash,
+            block_number,
+            taps,
+            revealed: false,
+        });
+        Ok(())
+    }
+
+    /// The `require`s of `revealTap` that depend on the block number, in contract order
+    pub fn check_reveal(&self, block_number: u64) -> Result<(), GameError> {
+        let commitment = self.commitment.ok_or(GameError::NoCommitment)?;
+        if commitment.revealed {
+            return Err(GameError::AlreadyRevealed);
+        }
+
+        let seed_block = commitment
+            .block_number
+            .saturating_add(self.config.commit_reveal_blocks);
+        if block_number < seed_block {
+            return Err(GameError::TooEarly);
+        }
+        if block_number == seed_block || block_number - seed_block > 256 {
+            return Err(GameError::BlockHashNotAvailable);
+        }
+        if block_number
+            > commitment
+                .block_number
+                .saturating_add(self.config.max_reveal_delay)
+        {
+            return Err(GameError::TooLate);
+        }
+        Ok(())
+    }
+
+    /// `revealTap`, returns the committed tap count to execute
+    pub fn reveal(
+        &mut self,
+        player: &Address,
+        secret: U256,
+        nonce: U256,
+        block_number: u64,
+    ) -> Result<u16, GameError> {
+        self.check_reveal(block_number)?;
+        let commitment = self.commitment.as_mut().ok_or(GameError::NoCommitment)?;
+        if !verify_commitment(&commitment.hash, player, secret, nonce, commitment.taps) {
+            return Err(GameError::InvalidReveal);
+        }
+
+        commitment.revealed = true;
+        Ok(commitment.taps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commit::commitment_hash;
+
+    fn player() -> Address {
+        "0x70997970C51812dc3A010C7d01b50e0d17dc79C8"
+            .parse()
+            .unwrap()
+    }
+
+    fn committed_at(block_number: u64) -> CommitTracker {
+        let mut tracker = CommitTracker::new(GameConfig::default());
+        let hash = commitment_hash(&player(), U256::from(1), U256::from(2), 10);
+        tracker.commit(hash, 10, block_number).unwrap();
+        tracker
+    }
+
+    #[test]
+    fn test_state_transitions() {
+        let tracker = CommitTracker::new(GameConfig::default());
+        assert_eq!(tracker.state(1), CommitState::Idle);
+
+        let tracker = committed_at(100);
+        assert_eq!(tracker.reveal_window(), Some((102, 356)));
+        assert_eq!(tracker.state(100), CommitState::Committed);
+        assert_eq!(tracker.state(101), CommitState::Committed);
+        assert_eq!(tracker.state(102), CommitState::Revealable);
+        assert_eq!(tracker.state(356), CommitState::Revealable);
+        assert_eq!(tracker.state(357), CommitState::Expired);
+    }
+
+    #[test]
+    fn test_status_reports_blocks_and_actions() {
+        let tracker = committed_at(100);
+
+        let status = tracker.status(100);
+        assert_eq!(status.blocks_until_revealable, 2);
+        assert_eq!(status.blocks_remaining, 0);
+        assert_eq!(status.allowed_actions, vec![CommitAction::TapMine]);
+
+        let status = tracker.status(300);
+        assert_eq!(status.blocks_remaining, 56);
+        assert_eq!(
+            status.allowed_actions,
+            vec![CommitAction::Reveal, CommitAction::TapMine]
+        );
+
+        assert_eq!(
+            tracker.status(400).allowed_actions,
+            vec![CommitAction::TapMine]
+        );
+    }
+
+    #[test]
+    fn test_second_commit_rejected_until_revealed() {
+        let mut tracker = committed_at(100);
+        let hash = H256([0x33; 32]);
+
+        assert_eq!(
+            tracker.commit(hash, 5, 101),
+            Err(GameError::PendingCommitment)
+        );
+        // An expired commitment still blocks `commitTap`
+        assert_eq!(
+            tracker.commit(hash, 5, 1000),
+            Err(GameError::PendingCommitment)
+        );
+
+        tracker
+            .reveal(&player(), U256::from(1), U256::from(2), 150)
+            .unwrap();
+        assert_eq!(tracker.state(151), CommitState::Revealed);
+        assert_eq!(tracker.commit(hash, 5, 151), Ok(()));
+    }
+
+    #[test]
+    fn test_reveal_errors_follow_contract_order() {
+        let mut tracker = CommitTracker::new(GameConfig::default());
+        assert_eq!(tracker.check_reveal(1), Err(GameError::NoCommitment));
+
+        let mut tracker_at_100 = committed_at(100);
+        assert_eq!(tracker_at_100.check_reveal(100), Err(GameError::TooEarly));
+        assert_eq!(
+            tracker_at_100.check_reveal(101),
+            Err(GameError::BlockHashNotAvailable)
+        );
+        assert_eq!(tracker_at_100.check_reveal(357), Err(GameError::TooLate));
+        assert_eq!(
+            tracker_at_100.reveal(&player(), U256::from(1), U256::from(3), 200),
+            Err(GameError::InvalidReveal)
+        );
+        assert_eq!(
+            tracker_at_100.reveal(&player(), U256::from(1), U256::from(2), 200),
+            Ok(10)
+        );
+        assert_eq!(
+            tracker_at_100.check_reveal(201),
+            Err(GameError::AlreadyRevealed)
+        );
+
+        assert_eq!(
+            tracker.commit(H256::zero(), 21, 1),
+            Err(GameError::InvalidTapCount)
+        );
+    }
+
+    #[test]
+    fn test_window_saturates_at_the_last_block() {
+        let tracker = committed_at(u64::MAX - 1);
+
+        assert_eq!(tracker.seed_block(), Some(u64::MAX));
+        assert_eq!(tracker.reveal_window(), Some((u64::MAX, u64::MAX)));
+        assert_eq!(tracker.state(u64::MAX - 1), CommitState::Committed);
+        assert_eq!(tracker.check_reveal(u64::MAX - 1), Err(GameError::TooEarly));
+        assert_eq!(
+            tracker.check_reveal(u64::MAX),
+            Err(GameError::BlockHashNotAvailable)
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! Economics of `MinerGame.upgrade`.
+//!
+//! Going from level `l` to `l + 1` burns `(l + 1) * UPGRADE_COST_MULTIPLIER`,
+//! and the level multiplies the whole miner power sum. The planner walks the
+//! levels one by one, buying each upgrade as soon as tap rewards cover it.
+//! Expected rewards are long-run averages over the pity cycle, in MINE.
+
+use alloc::vec::Vec;
+
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+
+use crate::amount::TokenAmount;
+use crate::config::GameConfig;
+use crate::distribution::tap_reward_distribution;
+use crate::error::GameError;
+use crate::power::{base_tap_reward, total_power, TapPath};
+use crate::taps::stationary_pity_distribution;
+
+/// One upgrade on the way to the target level
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UpgradeStep {
+    /// Level reached by this upgrade
+    pub level: u32,
+    pub cost: TokenAmount,
+    pub cumulative_cost: TokenAmount,
+    pub total_power: u128,
+    pub base_tap_reward: TokenAmount,
+    pub expected_reward_per_tap: f64,
+    /// Gain over the previous level, in MINE per tap
+    pub extra_reward_per_tap: f64,
+    /// Taps from now until this upgrade is affordable, earlier upgrades bought,
+    /// `None` when taps earn nothing
+    pub taps_to_afford: Option<u64>,
+    /// `taps_to_afford` at the given tap rate, `None` without a rate
+    pub seconds_to_afford: Option<f64>,
+    /// Taps at the new level for the extra reward to repay the cost
+    pub payback_taps: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UpgradePlan {
+    pub current_level: u32,
+    pub target_level: u32,
+    pub total_power: u128,
+    pub expected_reward_per_tap: f64,
+    pub total_cost: TokenAmount,
+    pub steps: Vec<UpgradeStep>,
+}
+
+/// Cost of the upgrade from `level` to `level + 1`
+pub fn upgrade_cost(config: &GameConfig, level: u32) -> Result<TokenAmount, GameError> {
+    config
+        .upgrade_cost_multiplier
+        .checked_mul(U256::from(level) + U256::one())
+        .ok_or(GameError::ArithmeticOverflow)
+}
+
+/// Total cost of going from `from_level` to `to_level`
+pub fn cumulative_upgrade_cost(
+    config: &GameConfig,
+    from_level: u32,
+    to_level: u32,
+) -> Result<TokenAmount, GameError> {
+    (from_level..to_level).try_fold(TokenAmount::ZERO, |total, level| {
+        total
+            .checked_add(upgrade_cost(config, level)?)
+            .ok_or(GameError::ArithmeticOverflow)
+    })
+}
+
+/// Long-run expected MINE per tap for a given base tap reward
+///
+/// Fails with `ArithmeticOverflow` when a tap reward overflows `uint256`.
+pub fn expected_reward_per_tap(
+    config: &GameConfig,
+    base_tap_reward: TokenAmount,
+) -> Result<f64, GameError> {
+    stationary_pity_distribution(config)
+        .iter()
+        .enumerate()
+        .map(|(state, share)| {
+            tap_reward_distribution(config, state as u64, base_tap_reward)
+                .map(|distribution| share * distribution.mean)
+        })
+        .sum()
+}
+
+/// Plan every upgrade from `current_level` to `target_level`
+///
+/// `miner_powers` are the powers of the registered miners the player owns
+/// and `taps_per_minute` the expected tap rate, 0 if unknown.
+pub fn plan_upgrades(
+    config: &GameConfig,
+    current_level: u32,
+    target_level: u32,
+    balance: TokenAmount,
+    miner_powers: &[u128],
+    taps_per_minute: f64,
+    path: TapPath,
+) -> Result<UpgradePlan, GameError> {
+    if target_level > config.max_level {
+        return Err(GameError::MaxLevelReached);
+    }
+
+    let reward_at = |level: u32| -> Result<(u128, TokenAmount, f64), GameError> {
+        let power = total_power(miner_powers, level)?;
+        let base = base_tap_reward(config, power, path)?;
+        Ok((power, base, expected_reward_per_tap(config, base)?))
+    };
+
+    let (power, _, mut reward) = reward_at(current_level)?;
+    let mut plan = UpgradePlan {
+        current_level,
+        target_level,
+        total_power: power,
+        expected_reward_per_tap: reward,
+        total_cost: TokenAmount::ZERO,
+        steps: Vec::new(),
+    };
+
+    let mut balance = balance.to_tokens_f64();
+    let mut taps = Some(0u64);
+    for level in current_level..target_level {
+        let cost = upgrade_cost(config, level)?;
+        let missing = cost.to_tokens_f64() - balance;
+        if missing > 0.0 {
+            if reward > 0.0 {
+                let needed = libm::ceil(missing / reward);
+                taps = taps.map(|taps| taps + needed as u64);
+                balance += needed * reward;
+            } else {
+                taps = None;
+            }
+        }
+        balance -= cost.to_tokens_f64();
+
+        let (power, base, next_reward) = reward_at(level + 1)?;
+        let extra = next_reward - reward;
+        plan.total_cost = plan
+            .total_cost
+            .checked_add(cost)
+            .ok_or(GameError::ArithmeticOverflow)?;
+        plan.steps.push(UpgradeStep {
+            level: level + 1,
+            cost,
+            cumulative_cost: plan.total_cost,
+            total_power: power,
+            base_tap_reward: base,
+            expected_reward_per_tap: next_reward,
+            extra_reward_per_tap: extra,
+            taps_to_afford: taps,
+            seconds_to_afford: taps
+                .filter(|_| taps_per_minute > 0.0)
+                .map(|taps| taps as f64 * 60.0 / taps_per_minute),
+            payback_taps: (extra > 0.0).then(|| cost.to_tokens_f64() / extra),
+        });
+        reward = next_reward;
+    }
+
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cumulative_cost() {
+        let config = GameConfig::default();
+
+        assert_eq!(upgrade_cost(&config, 0), Ok(TokenAmount::from_tokens(100)));
+        assert_eq!(
+            upgrade_cost(&config, 99),
+            Ok(TokenAmount::from_tokens(10_000))
+        );
+        // 100 * (1 + 2 + ... + 100)
+        assert_eq!(
+            cumulative_upgrade_cost(&config, 0, 100),
+            Ok(TokenAmount::from_tokens(505_000))
+        );
+        assert_eq!(
+            cumulative_upgrade_cost(&config, 3, 5),
+            Ok(TokenAmount::from_tokens(900))
+        );
+    }
+
+    #[test]
+    fn test_expected_reward_is_linear_in_base() {
+        let config = GameConfig::default();
+        let at_10 = expected_reward_per_tap(&config, TokenAmount::from_tokens(10)).unwrap();
+        let at_20 = expected_reward_per_tap(&config, TokenAmount::from_tokens(20)).unwrap();
+        let gems_only = expected_reward_per_tap(&config, TokenAmount::ZERO).unwrap();
+
+        assert!(gems_only > 0.0);
+        assert!(((at_20 - gems_only) - 2.0 * (at_10 - gems_only)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_expected_reward_pins_long_run_ev() {
+        // Default crits are all x2, about one tap in ten crits over the pity
+        // cycle, and gems add 5% of the mean bonus
+        let config = GameConfig::default();
+        let ev = expected_reward_per_tap(&config, TokenAmount::from_tokens(10)).unwrap();
+        let gems_only = expected_reward_per_tap(&config, TokenAmount::ZERO).unwrap();
+
+        assert!((ev - 25.752_157_146_925).abs() < 1e-9);
+        assert!((gems_only - 14.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_plan_steps() {
+        let config = GameConfig::default();
+        let plan = plan_upgrades(
+            &config,
+            0,
+            3,
+            TokenAmount::from_tokens(150),
+            &[4],
+            60.0,
+            TapPath::TapMine,
+        )
+        .unwrap();
+
+        assert_eq!(plan.total_power, 4);
+        assert_eq!(plan.total_cost, TokenAmount::from_tokens(600));
+        let levels: Vec<u32> = plan.steps.iter().map(|s| s.level).collect();
+        assert_eq!(levels, vec![1, 2, 3]);
+        assert_eq!(plan.steps[1].total_power, 12);
+        assert_eq!(plan.steps[1].base_tap_reward, TokenAmount::from_tokens(130));
+
+        // The first upgrade is already paid for by the balance
+        let taps: Vec<u64> = plan
+            .steps
+            .iter()
+            .map(|s| s.taps_to_afford.unwrap())
+            .collect();
+        assert_eq!(taps[0], 0);
+        assert!(taps[1] > 0);
+        assert!(taps[2] >= taps[1]);
+        assert_eq!(plan.steps[1].seconds_to_afford, Some(taps[1] as f64));
+        for step in &plan.steps {
+            assert!(step.extra_reward_per_tap > 0.0);
+            let payback = step.payback_taps.unwrap();
+            assert!((payback * step.extra_reward_per_tap - step.cost.to_tokens_f64()).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_plan_limits() {
+        let config = GameConfig::default();
+
+        assert_eq!(
+            plan_upgrades(
+                &config,
+                0,
+                101,
+                TokenAmount::ZERO,
+                &[],
+                0.0,
+                TapPath::TapMine
+            ),
+            Err(GameError::MaxLevelReached)
+        );
+
+        // Without miners the level multiplies nothing
+        let plan =
+            plan_upgrades(&config, 5, 6, TokenAmount::ZERO, &[], 0.0, TapPath::TapMine).unwrap();
+        assert_eq!(plan.steps[0].payback_taps, None);
+        assert_eq!(plan.steps[0].seconds_to_afford, None);
+
+        let free_taps = GameConfig {
+            base_reward: TokenAmount::ZERO,
+            gem_drop_chance: 0,
+            ..GameConfig::default()
+        };
+        let plan = plan_upgrades(
+            &free_taps,
+            0,
+            1,
+            TokenAmount::ZERO,
+            &[1],
+            60.0,
+            TapPath::TapMine,
+        )
+        .unwrap();
+        assert_eq!(plan.steps[0].taps_to_afford, None);
+    }
+}
@@ -0,0 +1,653 @@
+//! Golden vectors shared with the Hardhat suite.
+//!
+//! A vector pins down one `_executeTaps` call: the config, the entry point
+//! inputs the seed is hashed from and the player state going in, every tap
+//! and the player state coming out. The checked-in `vectors/miner-game.json`
+//! is replayed here through `execute_taps` and held against a transcription
+//! of the contract loop.
+//!
+//! `contracts/test/GoldenVectors.test.ts` plays each vector through
+//! `commitTap` + `revealTap` or `tapMine`. Everything but the block hash in
+//! [`SeedInputs`] can be forced on chain, so the suite swaps in the hash the
+//! chain picked (`tapforge vectors replay --block-hash`) and compares the
+//! events and state it reads with that replay.
+//! `generate_vectors` draws new vectors from a seed, staying within what the
+//! owner-only setters and `MinerNFT` can reproduce on chain.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use primitive_types::{H256, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::address::Address;
+use crate::amount::TokenAmount;
+use crate::config::GameConfig;
+use crate::error::GameError;
+use crate::gems::GemType;
+use crate::power::{base_tap_reward, total_power, TapPath};
+use crate::random::{FastRng, RandomSource};
+use crate::reveal::reveal_seed;
+use crate::taps::{execute_taps, pity_saturation, tap_mine_seed, TapOutcome};
+
+/// Version of the vector file format
+pub const VECTORS_VERSION: u32 = 2;
+
+/// What the entry point hashes into the seed it hands to `_executeTaps`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "path", rename_all = "snake_case")]
+pub enum SeedInputs {
+    /// `revealTap(secret, nonce)`, `block_hash` is `blockhash(commitBlock + 1)`
+    CommitReveal {
+        player: Address,
+        secret: U256,
+        nonce: U256,
+        block_hash: H256,
+    },
+    /// `tapMine` in a block at `timestamp`, `block_hash` is the parent's
+    TapMine {
+        player: Address,
+        timestamp: u64,
+        prev_randao: U256,
+        block_hash: H256,
+    },
+}
+
+impl SeedInputs {
+    pub fn path(&self) -> TapPath {
+        match self {
+            SeedInputs::CommitReveal { .. } => TapPath::CommitReveal,
+            SeedInputs::TapMine { .. } => TapPath::TapMine,
+        }
+    }
+
+    pub fn player(&self) -> &Address {
+        match self {
+            SeedInputs::CommitReveal { player, .. } | SeedInputs::TapMine { player, .. } => player,
+        }
+    }
+
+    /// Seed handed to `_executeTaps`, before the first re-hash
+    pub fn seed(&self) -> U256 {
+        match self {
+            SeedInputs::CommitReveal {
+                player,
+                secret,
+                nonce,
+                block_hash,
+            } => reveal_seed(block_hash, *secret, *nonce, player),
+            SeedInputs::TapMine {
+                player,
+                timestamp,
+                prev_randao,
+                block_hash,
+            } => tap_mine_seed(*timestamp, *prev_randao, player, block_hash),
+        }
+    }
+
+    /// The same inputs with the block hash the chain picked
+    pub fn with_block_hash(&self, hash: H256) -> SeedInputs {
+        let mut inputs = self.clone();
+        match &mut inputs {
+            SeedInputs::CommitReveal { block_hash, .. }
+            | SeedInputs::TapMine { block_hash, .. } => *block_hash = hash,
+        }
+        inputs
+    }
+}
+
+/// What goes into the call
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VectorInput {
+    pub seed_inputs: SeedInputs,
+    pub taps: u16,
+    pub level: u32,
+    /// Powers of the registered miners, all owned by the player
+    pub miner_powers: Vec<u128>,
+    pub taps_since_critical: u64,
+}
+
+/// Player fields written by the call, starting from a fresh player
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VectorPlayer {
+    pub total_power: u128,
+    pub pending_rewards: TokenAmount,
+    pub total_taps: u64,
+    pub critical_hits: u64,
+    pub taps_since_critical: u64,
+}
+
+/// What the contract does with the input
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VectorExpectation {
+    /// Seed handed to `_executeTaps`, before the first re-hash
+    pub seed: U256,
+    pub base_tap_reward: TokenAmount,
+    pub taps: Vec<TapOutcome>,
+    /// `reward` of the `Tapped` event
+    pub total_reward: TokenAmount,
+    /// `critical` of the `Tapped` event
+    pub has_critical: bool,
+    pub player: VectorPlayer,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GoldenVector {
+    pub name: String,
+    pub config: GameConfig,
+    pub input: VectorInput,
+    pub expected: VectorExpectation,
+}
+
+/// A vector file as written by `tapforge vectors generate`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VectorFile {
+    pub version: u32,
+    /// Seed the vectors were generated from
+    pub seed: u64,
+    pub vectors: Vec<GoldenVector>,
+}
+
+/// Why a vector does not replay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VectorError {
+    /// The call itself reverts
+    Reverted(GameError),
+    /// `field` differs from the expectation
+    Mismatch {
+        field: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl fmt::Display for VectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VectorError::Reverted(err) => write!(f, "call reverts: {err}"),
+            VectorError::Mismatch {
+                field,
+                expected,
+                actual,
+            } => write!(f, "{field}: expected {expected}, got {actual}"),
+        }
+    }
+}
+
+impl core::error::Error for VectorError {}
+
+impl From<GameError> for VectorError {
+    fn from(err: GameError) -> Self {
+        VectorError::Reverted(err)
+    }
+}
+
+/// Play `input` the way `_executeTaps` does for a fresh player
+pub fn replay(config: &GameConfig, input: &VectorInput) -> Result<VectorExpectation, GameError> {
+    config.check_tap_count(input.taps)?;
+    let power = total_power(&input.miner_powers, input.level)?;
+    let seed = input.seed_inputs.seed();
+    let base = base_tap_reward(config, power, input.seed_inputs.path())?;
+    let execution = execute_taps(config, seed, input.taps, input.taps_since_critical, base)?;
+
+    Ok(VectorExpectation {
+        seed,
+        base_tap_reward: base,
+        player: VectorPlayer {
+            total_power: power,
+            pending_rewards: execution.total_reward,
+            total_taps: input.taps as u64,
+            critical_hits: execution.critical_hits,
+            taps_since_critical: execution.taps_since_critical,
+        },
+        taps: execution.outcomes,
+        total_reward: execution.total_reward,
+        has_critical: execution.has_critical,
+    })
+}
+
+fn compare<T: PartialEq + fmt::Debug>(
+    field: impl ToString,
+    expected: &T,
+    actual: &T,
+) -> Result<(), VectorError> {
+    if expected == actual {
+        return Ok(());
+    }
+    Err(VectorError::Mismatch {
+        field: field.to_string(),
+        expected: format!("{expected:?}"),
+        actual: format!("{actual:?}"),
+    })
+}
+
+impl GoldenVector {
+    /// Vector whose expectation is the current replay of `input`
+    pub fn new(name: String, config: GameConfig, input: VectorInput) -> Result<Self, GameError> {
+        let expected = replay(&config, &input)?;
+        Ok(GoldenVector {
+            name,
+            config,
+            input,
+            expected,
+        })
+    }
+
+    /// The vector replayed with the block hash the chain picked, the only
+    /// seed input a Hardhat test cannot force
+    pub fn with_block_hash(&self, block_hash: H256) -> Result<Self, GameError> {
+        let input = VectorInput {
+            seed_inputs: self.input.seed_inputs.with_block_hash(block_hash),
+            ..self.input.clone()
+        };
+        GoldenVector::new(self.name.clone(), self.config.clone(), input)
+    }
+
+    /// Replay the vector, failing on the first field that differs
+    pub fn check(&self) -> Result<(), VectorError> {
+        let actual = replay(&self.config, &self.input)?;
+        let expected = &self.expected;
+
+        compare("seed", &expected.seed, &actual.seed)?;
+        compare(
+            "base_tap_reward",
+            &expected.base_tap_reward,
+            &actual.base_tap_reward,
+        )?;
+        compare("taps.len", &expected.taps.len(), &actual.taps.len())?;
+        for (index, (expected, actual)) in expected.taps.iter().zip(&actual.taps).enumerate() {
+            compare(format!("taps[{index}]"), expected, actual)?;
+        }
+        compare("total_reward", &expected.total_reward, &actual.total_reward)?;
+        compare("has_critical", &expected.has_critical, &actual.has_critical)?;
+        compare("player", &expected.player, &actual.player)
+    }
+}
+
+impl VectorFile {
+    pub fn from_json(json: &str) -> Result<VectorFile, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Check every vector, with the name of the first one that fails
+    pub fn check(&self) -> Result<(), (String, VectorError)> {
+        self.vectors
+            .iter()
+            .try_for_each(|vector| vector.check().map_err(|err| (vector.name.clone(), err)))
+    }
+
+    pub fn vector(&self, name: &str) -> Option<&GoldenVector> {
+        self.vectors.iter().find(|vector| vector.name == name)
+    }
+}
+
+/// Powers `MinerNFT.mintMiner` gives each rarity
+const RARITY_POWERS: [u128; 4] = [1, 3, 7, 15];
+
+fn below(rng: &mut FastRng, bound: u64) -> u64 {
+    rng.next_u64() % bound
+}
+
+/// Random split of 100 into `N` parts
+fn split_100<const N: usize>(rng: &mut FastRng) -> [u64; N] {
+    let mut parts = [0; N];
+    let mut left = 100;
+    for part in parts.iter_mut().take(N - 1) {
+        *part = below(rng, left + 1);
+        left -= *part;
+    }
+    parts[N - 1] = left;
+    parts
+}
+
+/// Default config or one retuned through `updateCriticalWeights` and
+/// `updateGemReward`, the only knobs the contract has
+fn random_config(rng: &mut FastRng) -> GameConfig {
+    let mut config = GameConfig::default();
+    if below(rng, 2) == 0 {
+        return config;
+    }
+
+    config.critical_weights = split_100(rng);
+    let drop_chances: [u64; 4] = split_100(rng);
+    for (gem, drop_chance) in GemType::ALL.into_iter().zip(drop_chances) {
+        let reward = match gem {
+            GemType::Ruby => &mut config.gem_rewards.ruby,
+            GemType::Sapphire => &mut config.gem_rewards.sapphire,
+            GemType::Diamond => &mut config.gem_rewards.diamond,
+        };
+        reward.drop_chance = drop_chance;
+        reward.bonus = TokenAmount::from_tokens(below(rng, 10_001));
+    }
+    config
+}
+
+fn random_hash(rng: &mut FastRng) -> H256 {
+    H256(rng.next_word(0).to_big_endian())
+}
+
+fn random_seed_inputs(rng: &mut FastRng) -> SeedInputs {
+    let mut player = [0u8; 20];
+    player.copy_from_slice(&random_hash(rng)[..20]);
+    let player = Address(player);
+
+    if below(rng, 2) == 0 {
+        SeedInputs::CommitReveal {
+            player,
+            secret: rng.next_word(0),
+            nonce: rng.next_word(0),
+            block_hash: random_hash(rng),
+        }
+    } else {
+        SeedInputs::TapMine {
+            player,
+            // 2033 to 2036, ahead of any chain the suite starts
+            timestamp: 2_000_000_000 + below(rng, 100_000_000),
+            prev_randao: rng.next_word(0),
+            block_hash: random_hash(rng),
+        }
+    }
+}
+
+fn random_input(rng: &mut FastRng, config: &GameConfig) -> VectorInput {
+    let miner_powers = (0..below(rng, 4))
+        .map(|_| {
+            let base = RARITY_POWERS[below(rng, 4) as usize];
+            // Some miners went through `upgradeMiner`
+            let upgraded = if below(rng, 4) == 0 {
+                below(rng, 100) as u128
+            } else {
+                0
+            };
+            base + upgraded
+        })
+        .collect();
+
+    // Bias the pity counter towards the edges of the bonus ramp
+    let saturation = pity_saturation(config);
+    let taps_since_critical = match below(rng, 4) {
+        0 => below(rng, 10),
+        1 => config.pity_threshold.saturating_sub(3) + below(rng, 8),
+        2 => saturation.saturating_sub(3) + below(rng, 8),
+        _ => below(rng, saturation + 50),
+    };
+
+    VectorInput {
+        seed_inputs: random_seed_inputs(rng),
+        taps: 1 + below(rng, config.max_taps_per_call as u64) as u16,
+        level: match below(rng, 8) {
+            0 => config.max_level,
+            _ => below(rng, 11) as u32,
+        },
+        miner_powers,
+        taps_since_critical,
+    }
+}
+
+/// `count` random vectors drawn from `seed`, the same ones for the same seed
+pub fn generate_vectors(seed: u64, count: usize) -> VectorFile {
+    let mut rng = FastRng::new(seed);
+    let vectors = (0..count)
+        .map(|index| {
+            let config = random_config(&mut rng);
+            let input = random_input(&mut rng, &config);
+            GoldenVector::new(format!("random-{index}"), config, input)
+                .expect("generated inputs stay within the contract limits")
+        })
+        .collect();
+
+    VectorFile {
+        version: VECTORS_VERSION,
+        seed,
+        vectors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sha3::{Digest, Keccak256};
+
+    use super::*;
+
+    const GOLDEN: &str = include_str!("../../vectors/miner-game.json");
+
+    fn golden() -> VectorFile {
+        VectorFile::from_json(GOLDEN).unwrap()
+    }
+
+    #[test]
+    fn test_golden_vectors_replay() {
+        let file = golden();
+        assert_eq!(file.version, VECTORS_VERSION);
+        assert_eq!(file.check(), Ok(()));
+    }
+
+    /// The `abi.encodePacked` seeds of `revealTap` and `tapMine`, written out
+    /// like `contract_taps`
+    fn contract_seed(inputs: &SeedInputs) -> U256 {
+        let mut packed = Vec::new();
+        match inputs {
+            SeedInputs::CommitReveal {
+                player,
+                secret,
+                nonce,
+                block_hash,
+            } => {
+                packed.extend_from_slice(block_hash.as_bytes());
+                packed.extend_from_slice(&secret.to_big_endian());
+                packed.extend_from_slice(&nonce.to_big_endian());
+                packed.extend_from_slice(player.as_bytes());
+            }
+            SeedInputs::TapMine {
+                player,
+                timestamp,
+                prev_randao,
+                block_hash,
+            } => {
+                packed.extend_from_slice(&U256::from(*timestamp).to_big_endian());
+                packed.extend_from_slice(&prev_randao.to_big_endian());
+                packed.extend_from_slice(player.as_bytes());
+                packed.extend_from_slice(block_hash.as_bytes());
+            }
+        }
+        U256::from_big_endian(&Keccak256::digest(&packed))
+    }
+
+    /// `_executeTaps` transcribed from `MinerGame.sol` without going through
+    /// `taps`, so the pinned expectations are held against the contract and
+    /// not against the code that wrote them
+    fn contract_taps(
+        config: &GameConfig,
+        input: &VectorInput,
+        base: U256,
+    ) -> (Vec<TapOutcome>, u64) {
+        let hundred = U256::from(100);
+        let mut random_seed = contract_seed(&input.seed_inputs);
+        let mut taps_since_critical = input.taps_since_critical;
+        let mut taps = Vec::new();
+
+        for i in 0..input.taps {
+            let mut packed = [0u8; 34];
+            random_seed.write_as_big_endian(&mut packed[..32]);
+            packed[32..].copy_from_slice(&i.to_be_bytes());
+            random_seed = U256::from_big_endian(&Keccak256::digest(packed));
+            let random = (random_seed % hundred).as_u64();
+
+            let pity_bonus = if taps_since_critical <= config.pity_threshold {
+                0
+            } else {
+                ((taps_since_critical - config.pity_threshold) * config.pity_increment)
+                    .min(config.max_pity_bonus)
+            };
+            let crit_chance = config.critical_base_chance + pity_bonus;
+
+            let mut tap = TapOutcome {
+                index: i,
+                seed: random_seed,
+                critical_chance: crit_chance,
+                is_critical: random < crit_chance,
+                multiplier: 1,
+                gem: None,
+                gem_bonus: TokenAmount::ZERO,
+                reward: TokenAmount::from(base),
+            };
+
+            if tap.is_critical {
+                // _selectMultiplier
+                let mut cumulative = 0;
+                tap.multiplier = config.critical_multipliers[0];
+                for i in 0..4 {
+                    cumulative += config.critical_weights[i];
+                    if random < cumulative {
+                        tap.multiplier = config.critical_multipliers[i];
+                        break;
+                    }
+                }
+                taps_since_critical = 0;
+
+                // _selectGem(randomSeed >> 8)
+                if random < 5 {
+                    let mut gem_roll = ((random_seed >> 8) % hundred).as_u64();
+                    for gem in [GemType::Ruby, GemType::Sapphire, GemType::Diamond] {
+                        let reward = config.gem_rewards.get(gem);
+                        if gem_roll < reward.drop_chance {
+                            tap.gem = Some(gem);
+                            tap.gem_bonus = reward.bonus;
+                            break;
+                        }
+                        gem_roll -= reward.drop_chance;
+                    }
+                }
+
+                tap.reward =
+                    TokenAmount::from(base * U256::from(tap.multiplier) + tap.gem_bonus.wei());
+            } else {
+                taps_since_critical += 1;
+            }
+
+            taps.push(tap);
+        }
+
+        (taps, taps_since_critical)
+    }
+
+    #[test]
+    fn test_golden_vectors_match_the_contract_loop() {
+        for vector in golden().vectors {
+            let (config, input, expected) = (&vector.config, &vector.input, &vector.expected);
+
+            let power = input.miner_powers.iter().sum::<u128>() * (input.level as u128 + 1);
+            let mut base = config.base_reward.wei() * U256::from(power + 1);
+            if matches!(input.seed_inputs, SeedInputs::CommitReveal { .. }) {
+                base = base * 110 / 100;
+            }
+            let (taps, taps_since_critical) = contract_taps(config, input, base);
+            let total_reward = taps
+                .iter()
+                .fold(U256::zero(), |total, tap| total + tap.reward.wei());
+            let critical_hits = taps.iter().filter(|tap| tap.is_critical).count() as u64;
+
+            let name = &vector.name;
+            assert_eq!(expected.seed, contract_seed(&input.seed_inputs), "{name}");
+            assert_eq!(expected.base_tap_reward.wei(), base, "{name}");
+            assert_eq!(expected.taps, taps, "{name}");
+            assert_eq!(expected.total_reward.wei(), total_reward, "{name}");
+            assert_eq!(expected.has_critical, critical_hits > 0, "{name}");
+            assert_eq!(
+                expected.player,
+                VectorPlayer {
+                    total_power: power,
+                    pending_rewards: TokenAmount::from(total_reward),
+                    total_taps: input.taps as u64,
+                    critical_hits,
+                    taps_since_critical,
+                },
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_golden_file_is_reproducible() {
+        // Regenerate with `tapforge vectors generate` after a deliberate change
+        let file = golden();
+        assert_eq!(generate_vectors(file.seed, file.vectors.len()), file);
+    }
+
+    #[test]
+    fn test_golden_vectors_cover_the_mechanics() {
+        let file = golden();
+        let vectors = &file.vectors;
+        let taps = || vectors.iter().flat_map(|v| &v.expected.taps);
+
+        let path = |v: &GoldenVector| v.input.seed_inputs.path();
+        assert!(vectors.iter().any(|v| path(v) == TapPath::CommitReveal));
+        assert!(vectors.iter().any(|v| path(v) == TapPath::TapMine));
+        assert!(vectors.iter().any(|v| v.config != GameConfig::default()));
+        assert!(vectors.iter().any(|v| v.expected.player.total_power > 0));
+        assert!(taps().any(|tap| tap.critical_chance > 10));
+        assert!(taps().any(|tap| tap.is_critical && tap.multiplier > 2));
+        for gem in GemType::ALL {
+            assert!(taps().any(|tap| tap.gem == Some(gem)), "no {gem:?}");
+        }
+    }
+
+    #[test]
+    fn test_check_reports_the_first_difference() {
+        let mut vector = golden().vectors.swap_remove(0);
+        vector.expected.taps[0].reward += TokenAmount::from(1);
+        vector.expected.total_reward += TokenAmount::from(1);
+
+        match vector.check() {
+            Err(VectorError::Mismatch { field, .. }) => assert_eq!(field, "taps[0]"),
+            other => panic!("unexpected {other:?}"),
+        }
+
+        vector.input.taps = 0;
+        assert_eq!(
+            vector.check(),
+            Err(VectorError::Reverted(GameError::InvalidTapCount))
+        );
+    }
+
+    #[test]
+    fn test_with_block_hash_replays_the_chain_seed() {
+        let file = golden();
+        let vector = file.vector("random-0").unwrap();
+        let hash = H256([0x5a; 32]);
+
+        let replayed = vector.with_block_hash(hash).unwrap();
+        assert_eq!(replayed.check(), Ok(()));
+        assert_eq!(
+            replayed.input.seed_inputs,
+            vector.input.seed_inputs.with_block_hash(hash)
+        );
+        assert_eq!(
+            replayed.expected.seed,
+            contract_seed(&replayed.input.seed_inputs)
+        );
+        assert_ne!(replayed.expected.seed, vector.expected.seed);
+        // Nothing but the taps depends on the seed
+        assert_eq!(
+            replayed.expected.base_tap_reward,
+            vector.expected.base_tap_reward
+        );
+        assert_eq!(
+            replayed.expected.player.total_power,
+            vector.expected.player.total_power
+        );
+        assert!(file.vector("random-999").is_none());
+    }
+
+    #[test]
+    fn test_generated_configs_are_valid() {
+        for vector in generate_vectors(1, 64).vectors {
+            assert_eq!(vector.config.validate(), Ok(()));
+            assert!(vector.input.taps_since_critical < 200);
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! Command line parsing, without a parser crate.
+//!
+//! Options are pulled out by name as each command needs them, `--name value`
+//! or `--name=value`, and `finish` rejects whatever is left over.
+
+use std::fmt;
+use std::str::FromStr;
+
+use primitive_types::U256;
+use tap_forge_wasm::amount::{TokenAmount, WEI_PER_TOKEN};
+
+/// Why a command could not run
+#[derive(Debug)]
+pub enum CliError {
+    /// Bad invocation, reported with the usage text
+    Usage(String),
+    Failed(Box<dyn std::error::Error>),
+}
+
+impl<E: std::error::Error + 'static> From<E> for CliError {
+    fn from(err: E) -> Self {
+        CliError::Failed(Box::new(err))
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(message) => f.write_str(message),
+            CliError::Failed(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+pub fn usage(message: impl Into<String>) -> CliError {
+    CliError::Usage(message.into())
+}
+
+pub struct Args {
+    rest: Vec<String>,
+}
+
+impl Args {
+    pub fn new(args: impl IntoIterator<Item = String>) -> Self {
+        Args {
+            rest: args.into_iter().collect(),
+        }
+    }
+
+    /// Next word if it is not an option
+    pub fn positional(&mut self) -> Option<String> {
+        if self.rest.first()?.starts_with("--") {
+            return None;
+        }
+        Some(self.rest.remove(0))
+    }
+
+    pub fn flag(&mut self, name: &str) -> bool {
+        let option = format!("--{}", name);
+        match self.rest.iter().position(|arg| *arg == option) {
+            Some(index) => {
+                self.rest.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn value(&mut self, name: &str) -> Result<Option<String>, CliError> {
+        let option = format!("--{}", name);
+        let prefix = format!("{}=", option);
+        for index in 0..self.rest.len() {
+            if let Some(value) = self.rest[index].strip_prefix(&prefix) {
+                let value = value.to_string();
+                self.rest.remove(index);
+                return Ok(Some(value));
+            }
+            if self.rest[index] == option {
+                if index + 1 == self.rest.len() {
+                    return Err(usage(format!("{} needs a value", option)));
+                }
+                let value = self.rest.remove(index + 1);
+                self.rest.remove(index);
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn required(&mut self, name: &str) -> Result<String, CliError> {
+        self.value(name)?
+            .ok_or_else(|| usage(format!("missing --{}", name)))
+    }
+
+    pub fn parsed<T: FromStr>(&mut self, name: &str) -> Result<Option<T>, CliError> {
+        self.value(name)?
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| usage(format!("invalid --{} `{}`", name, value)))
+            })
+            .transpose()
+    }
+
+    pub fn parsed_or<T: FromStr>(&mut self, name: &str, default: T) -> Result<T, CliError> {
+        Ok(self.parsed(name)?.unwrap_or(default))
+    }
+
+    /// Comma separated list, empty if absent
+    pub fn list<T: FromStr>(&mut self, name: &str) -> Result<Vec<T>, CliError> {
+        let Some(value) = self.value(name)? else {
+            return Ok(Vec::new());
+        };
+        value
+            .split(',')
+            .map(|item| {
+                item.trim()
+                    .parse()
+                    .map_err(|_| usage(format!("invalid --{} item `{}`", name, item)))
+            })
+            .collect()
+    }
+
+    /// Hex or decimal 256-bit word
+    pub fn word(&mut self, name: &str) -> Result<Option<U256>, CliError> {
+        self.value(name)?
+            .map(|value| {
+                tap_forge_wasm::commit::parse_word(&value)
+                    .ok_or_else(|| usage(format!("invalid --{} `{}`", name, value)))
+            })
+            .transpose()
+    }
+
+    /// Amount in MINE, e.g. `12.5`
+    pub fn mine(&mut self, name: &str) -> Result<Option<TokenAmount>, CliError> {
+        self.value(name)?
+            .map(|value| {
+                parse_mine(&value).ok_or_else(|| usage(format!("invalid --{} `{}`", name, value)))
+            })
+            .transpose()
+    }
+
+    /// Fail on anything no command asked for
+    pub fn finish(self) -> Result<(), CliError> {
+        match self.rest.first() {
+            Some(arg) => Err(usage(format!("unexpected argument `{}`", arg))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Decimal MINE amount with up to 18 decimals, exact to the wei
+pub fn parse_mine(value: &str) -> Option<TokenAmount> {
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    let digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if whole.is_empty() || fraction.len() > 18 || !digits(whole) || !digits(fraction) {
+        return None;
+    }
+
+    let whole = U256::from_dec_str(whole).ok()?;
+    let fraction = if fraction.is_empty() {
+        U256::zero()
+    } else {
+        U256::from_dec_str(fraction).ok()? * U256::exp10(18 - fraction.len())
+    };
+    let wei = whole
+        .checked_mul(U256::from(WEI_PER_TOKEN))?
+        .checked_add(fraction)?;
+    Some(TokenAmount::from(wei))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Args {
+        Args::new(line.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn test_options_in_any_order() {
+        let mut args = args("simulate sessions --taps=50 --fast --sessions 10");
+        assert_eq!(args.positional().as_deref(), Some("simulate"));
+        assert_eq!(args.positional().as_deref(), Some("sessions"));
+        assert_eq!(args.parsed::<u32>("sessions").unwrap(), Some(10));
+        assert!(args.flag("fast"));
+        assert!(!args.flag("fast"));
+        assert_eq!(args.parsed_or::<u32>("taps", 100).unwrap(), 50);
+        assert_eq!(args.parsed_or::<u32>("bins", 20).unwrap(), 20);
+        assert!(args.finish().is_ok());
+    }
+
+    #[test]
+    fn test_bad_input_is_a_usage_error() {
+        let mut missing = args("--taps");
+        assert!(matches!(missing.value("taps"), Err(CliError::Usage(_))));
+
+        let mut invalid = args("--taps many --miners 1,x");
+        assert!(matches!(
+            invalid.parsed::<u32>("taps"),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(
+            invalid.list::<u64>("miners"),
+            Err(CliError::Usage(_))
+        ));
+
+        let leftover = args("--unknown 1");
+        assert!(matches!(leftover.finish(), Err(CliError::Usage(_))));
+    }
+
+    #[test]
+    fn test_parse_mine() {
+        assert_eq!(parse_mine("10"), Some(TokenAmount::from_tokens(10)));
+        assert_eq!(
+            parse_mine("0.000000000000000001"),
+            Some(TokenAmount::from(1))
+        );
+        assert_eq!(
+            parse_mine("12.5"),
+            Some(TokenAmount::from(12_500_000_000_000_000_000))
+        );
+        assert_eq!(parse_mine(".5"), None);
+        assert_eq!(parse_mine("1.0000000000000000001"), None);
+        assert_eq!(parse_mine("-1"), None);
+    }
+}
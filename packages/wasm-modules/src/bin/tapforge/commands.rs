@@ -0,0 +1,597 @@
+//! The subcommands, each a thin layer over the library.
+
+use primitive_types::H256;
+use serde::Serialize;
+use tap_forge_wasm::address::Address;
+use tap_forge_wasm::amount::TokenAmount;
+use tap_forge_wasm::commit::{commitment_hash, parse_hash, CommitmentBundle};
+use tap_forge_wasm::config::GameConfig;
+use tap_forge_wasm::distribution::{tap_reward_distribution, TapRewardDistribution};
+use tap_forge_wasm::montecarlo::{simulate_sessions, MonteCarloParams, Percentiles};
+use tap_forge_wasm::pity::{PityChain, SessionForecast};
+use tap_forge_wasm::player::PlayerData;
+use tap_forge_wasm::power::{base_tap_reward, TapPath};
+use tap_forge_wasm::random::SourceKind;
+use tap_forge_wasm::reveal::{compute_reveal, RevealOutcome};
+use tap_forge_wasm::upgrade::{expected_reward_per_tap, plan_upgrades};
+use tap_forge_wasm::vectors::{generate_vectors, VectorFile};
+
+use crate::args::{usage, Args, CliError};
+use crate::output::{float, mine, optional, percent, Report, Table};
+
+fn path(args: &mut Args) -> TapPath {
+    if args.flag("commit-reveal") {
+        TapPath::CommitReveal
+    } else {
+        TapPath::TapMine
+    }
+}
+
+fn address(args: &mut Args) -> Result<Address, CliError> {
+    let value = args.required("address")?;
+    value
+        .parse()
+        .map_err(|_| usage(format!("invalid --address `{}`", value)))
+}
+
+fn hash(args: &mut Args, name: &str) -> Result<Option<H256>, CliError> {
+    args.value(name)?
+        .map(|value| {
+            parse_hash(&value).ok_or_else(|| usage(format!("invalid --{} `{}`", name, value)))
+        })
+        .transpose()
+}
+
+/// `simulate sessions`: Monte Carlo over many seeded sessions
+pub fn simulate_sessions_command(args: &mut Args, config: &GameConfig) -> Result<Report, CliError> {
+    let defaults = MonteCarloParams::default();
+    let params = MonteCarloParams {
+        sessions: args.parsed_or("sessions", defaults.sessions)?,
+        taps: args.parsed_or("taps", defaults.taps)?,
+        seed: args.word("seed")?.unwrap_or(defaults.seed),
+        taps_since_critical: args.parsed_or("taps-since-critical", 0)?,
+        total_power: args.parsed_or("power", 0)?,
+        path: path(args),
+        histogram_bins: args.parsed_or("bins", defaults.histogram_bins)?,
+        source: if args.flag("fast") {
+            SourceKind::Fast
+        } else {
+            SourceKind::Keccak
+        },
+    };
+    let report = simulate_sessions(config, &params)?;
+
+    let mut summary = Table::fields().titled("Sessions");
+    summary
+        .field("sessions", params.sessions)
+        .field("taps per session", params.taps)
+        .field("mean reward (MINE)", float(report.mean_reward))
+        .field("max tap reward (MINE)", mine(report.max_tap_reward))
+        .field("worst dry streak", report.worst_dry_streak)
+        .field("rubies", report.gems.ruby)
+        .field("sapphires", report.gems.sapphire)
+        .field("diamonds", report.gems.diamond);
+
+    let mut spread = Table::new(&["per session", "min", "p5", "p50", "p95", "p99", "max"]);
+    let mut add = |label: &str, p: &Percentiles| {
+        spread.row(
+            [p.min, p.p5, p.p50, p.p95, p.p99, p.max]
+                .iter()
+                .map(|value| float(*value))
+                .fold(vec![label.to_string()], |mut row, cell| {
+                    row.push(cell);
+                    row
+                }),
+        );
+    };
+    add("reward (MINE)", &report.reward);
+    add("criticals", &report.criticals);
+    add("pity resets", &report.pity_resets);
+    add("longest dry streak", &report.longest_dry_streak);
+
+    Ok(Report::new(&report)?.table(summary).table(spread))
+}
+
+/// `simulate economy`: upgrade plan paid for by tap rewards
+pub fn simulate_economy_command(args: &mut Args, config: &GameConfig) -> Result<Report, CliError> {
+    let level = args.parsed_or("level", 0)?;
+    let target = args.parsed_or("target", level + 1)?;
+    let balance = args.mine("balance")?.unwrap_or(TokenAmount::ZERO);
+    let miners: Vec<u128> = args.list("miners")?;
+    let taps_per_minute = args.parsed_or("taps-per-minute", 0.0)?;
+    let path = path(args);
+
+    let plan = plan_upgrades(
+        config,
+        level,
+        target,
+        balance,
+        &miners,
+        taps_per_minute,
+        path,
+    )?;
+
+    let mut summary = Table::fields().titled("Upgrades");
+    summary
+        .field(
+            "levels",
+            format!("{} -> {}", plan.current_level, plan.target_level),
+        )
+        .field("total power now", plan.total_power)
+        .field("EV per tap now (MINE)", float(plan.expected_reward_per_tap))
+        .field("total cost (MINE)", mine(plan.total_cost));
+
+    let mut steps = Table::new(&[
+        "level",
+        "cost",
+        "cumulative",
+        "power",
+        "base reward",
+        "EV/tap",
+        "taps to afford",
+        "payback taps",
+    ]);
+    for step in &plan.steps {
+        steps.row(vec![
+            step.level.to_string(),
+            mine(step.cost),
+            mine(step.cumulative_cost),
+            step.total_power.to_string(),
+            mine(step.base_tap_reward),
+            float(step.expected_reward_per_tap),
+            optional(step.taps_to_afford),
+            optional(step.payback_taps.map(|taps| taps.ceil())),
+        ]);
+    }
+
+    Ok(Report::new(&plan)?.table(summary).table(steps))
+}
+
+/// `commit`: commitment bundle for `commitTap`, random unless both words given
+pub fn commit_command(args: &mut Args, config: &GameConfig) -> Result<Report, CliError> {
+    let player = address(args)?;
+    let taps = args.parsed_or("taps", config.max_taps_per_call)?;
+    let block = args.parsed_or("block", 0)?;
+    config.check_tap_count(taps)?;
+
+    let bundle = match (args.word("secret")?, args.word("nonce")?) {
+        (Some(secret), Some(nonce)) => CommitmentBundle::new(player, secret, nonce, taps, block),
+        (None, None) => CommitmentBundle::generate(player, taps, block)?,
+        _ => return Err(usage("--secret and --nonce go together")),
+    };
+
+    let mut table = Table::fields().titled("Commitment");
+    table
+        .field("player", bundle.player)
+        .field("taps", bundle.taps)
+        .field("secret", format!("{:#x}", bundle.secret))
+        .field("nonce", format!("{:#x}", bundle.nonce))
+        .field("hash", format!("{:#x}", bundle.hash))
+        .field("created at block", bundle.created_at_block);
+
+    Ok(Report::new(&bundle)?.table(table))
+}
+
+#[derive(Serialize)]
+pub struct RevealCheck {
+    pub commitment: H256,
+    /// Whether `commitment` is the one given, `None` if none was
+    pub matches: Option<bool>,
+    pub outcome: RevealOutcome,
+}
+
+/// `verify-reveal`: replay `revealTap` from the opening and the block hash
+pub fn verify_reveal_command(args: &mut Args, config: &GameConfig) -> Result<Report, CliError> {
+    let player_address = address(args)?;
+    let secret = args
+        .word("secret")?
+        .ok_or_else(|| usage("missing --secret"))?;
+    let nonce = args
+        .word("nonce")?
+        .ok_or_else(|| usage("missing --nonce"))?;
+    let taps = args
+        .parsed("taps")?
+        .ok_or_else(|| usage("missing --taps"))?;
+    let block_hash = hash(args, "block-hash")?.ok_or_else(|| usage("missing --block-hash"))?;
+    let expected = hash(args, "commitment")?;
+
+    let mut player = match args.value("player")? {
+        Some(file) => serde_json::from_str(&std::fs::read_to_string(file)?)?,
+        None => PlayerData::default(),
+    };
+    if let Some(power) = args.parsed("power")? {
+        player.total_power = power;
+    }
+    if let Some(taps_since_critical) = args.parsed("taps-since-critical")? {
+        player.taps_since_critical = taps_since_critical;
+    }
+
+    let commitment = commitment_hash(&player_address, secret, nonce, taps);
+    let outcome = compute_reveal(
+        config,
+        &block_hash,
+        &player_address,
+        secret,
+        nonce,
+        taps,
+        &player,
+    )?;
+    let check = RevealCheck {
+        commitment,
+        matches: expected.map(|expected| expected == commitment),
+        outcome,
+    };
+
+    let execution = &check.outcome.execution;
+    let mut summary = Table::fields().titled("Reveal");
+    summary
+        .field("commitment", format!("{:#x}", check.commitment))
+        .field(
+            "matches",
+            optional(check.matches.map(|ok| if ok { "yes" } else { "NO" })),
+        )
+        .field("seed", format!("{:#x}", check.outcome.seed))
+        .field("total reward (MINE)", mine(execution.total_reward))
+        .field("critical hits", execution.critical_hits)
+        .field("taps since critical", execution.taps_since_critical)
+        .field(
+            "pending rewards (MINE)",
+            mine(check.outcome.player.pending_rewards),
+        );
+
+    let mut taps_table = Table::new(&["tap", "chance", "critical", "multiplier", "gem", "reward"]);
+    for tap in &execution.outcomes {
+        taps_table.row(vec![
+            tap.index.to_string(),
+            format!("{}%", tap.critical_chance),
+            if tap.is_critical { "yes" } else { "" }.to_string(),
+            format!("x{}", tap.multiplier),
+            optional(tap.gem.map(|gem| format!("{:?}", gem))),
+            mine(tap.reward),
+        ]);
+    }
+
+    let mut report = Report::new(&check)?.table(summary).table(taps_table);
+    report.failed = check.matches == Some(false);
+    Ok(report)
+}
+
+#[derive(Serialize)]
+pub struct ExpectedValues {
+    pub base_tap_reward: TokenAmount,
+    pub next_tap: TapRewardDistribution,
+    /// Mean taps up to and including the next crit
+    pub expected_taps_to_critical: Option<f64>,
+    pub session: SessionForecast,
+    /// Average over the whole pity cycle, in MINE
+    pub long_run_reward_per_tap: f64,
+}
+
+/// `ev`: exact expected values, no sampling
+pub fn ev_command(args: &mut Args, config: &GameConfig) -> Result<Report, CliError> {
+    let power = args.parsed_or("power", 0)?;
+    let taps_since_critical = args.parsed_or("taps-since-critical", 0)?;
+    let taps = args.parsed_or("taps", 100)?;
+    let base = base_tap_reward(config, power, path(args))?;
+
+    let chain = PityChain::new(config.clone());
+    let values = ExpectedValues {
+        base_tap_reward: base,
+        next_tap: tap_reward_distribution(config, taps_since_critical, base)?,
+        expected_taps_to_critical: chain
+            .taps_until_critical(taps_since_critical, 0)
+            .expected_taps,
+        session: chain.forecast(taps_since_critical, taps, base)?,
+        long_run_reward_per_tap: expected_reward_per_tap(config, base)?,
+    };
+
+    let next = &values.next_tap;
+    let mut summary = Table::fields().titled("Expected values");
+    summary
+        .field("base tap reward (MINE)", mine(values.base_tap_reward))
+        .field("critical chance", format!("{}%", next.critical_chance))
+        .field("next tap EV (MINE)", float(next.mean))
+        .field("next tap std dev (MINE)", float(next.standard_deviation))
+        .field(
+            "taps to next critical",
+            optional(values.expected_taps_to_critical.map(float)),
+        )
+        .field(
+            format!("EV over {} taps (MINE)", taps).as_str(),
+            float(values.session.expected_reward),
+        )
+        .field(
+            format!("criticals over {} taps", taps).as_str(),
+            float(values.session.expected_criticals),
+        )
+        .field(
+            "long-run EV per tap (MINE)",
+            float(values.long_run_reward_per_tap),
+        );
+
+    let mut outcomes = Table::new(&["reward", "critical", "multiplier", "gem", "probability"]);
+    for outcome in &next.outcomes {
+        outcomes.row(vec![
+            mine(outcome.reward),
+            if outcome.is_critical { "yes" } else { "" }.to_string(),
+            format!("x{}", outcome.multiplier),
+            optional(outcome.gem.map(|gem| format!("{:?}", gem))),
+            percent(outcome.probability),
+        ]);
+    }
+
+    Ok(Report::new(&values)?.table(summary).table(outcomes))
+}
+
+/// `vectors generate`: random golden vectors for the Rust and Hardhat suites
+pub fn vectors_generate_command(args: &mut Args) -> Result<Report, CliError> {
+    let seed = args.parsed_or("seed", 1)?;
+    let count = args.parsed_or("count", 32)?;
+    let file = generate_vectors(seed, count);
+
+    let mut table = Table::new(&[
+        "vector",
+        "path",
+        "power",
+        "taps",
+        "since critical",
+        "criticals",
+        "gems",
+        "reward",
+    ]);
+    for vector in &file.vectors {
+        let expected = &vector.expected;
+        table.row(vec![
+            vector.name.clone(),
+            format!("{:?}", vector.input.seed_inputs.path()),
+            expected.player.total_power.to_string(),
+            vector.input.taps.to_string(),
+            vector.input.taps_since_critical.to_string(),
+            expected.player.critical_hits.to_string(),
+            expected
+                .taps
+                .iter()
+                .filter(|tap| tap.gem.is_some())
+                .count()
+                .to_string(),
+            mine(expected.total_reward),
+        ]);
+    }
+
+    Ok(Report::new(&file)?.table(table))
+}
+
+/// `vectors replay`: one vector of a file, optionally with the block hash a
+/// chain picked in place of the pinned one
+pub fn vectors_replay_command(args: &mut Args) -> Result<Report, CliError> {
+    let path = args.required("file")?;
+    let name = args.required("name")?;
+    let block_hash = hash(args, "block-hash")?;
+
+    let file = VectorFile::from_json(&std::fs::read_to_string(&path)?)?;
+    let vector = file
+        .vector(&name)
+        .ok_or_else(|| usage(format!("no vector `{}` in {}", name, path)))?;
+    let vector = match block_hash {
+        Some(block_hash) => vector.with_block_hash(block_hash)?,
+        None => vector.clone(),
+    };
+
+    let expected = &vector.expected;
+    let mut summary = Table::fields().titled(&vector.name);
+    summary
+        .field("path", format!("{:?}", vector.input.seed_inputs.path()))
+        .field("seed", format!("{:#x}", expected.seed))
+        .field("total reward (MINE)", mine(expected.total_reward))
+        .field("critical hits", expected.player.critical_hits)
+        .field("taps since critical", expected.player.taps_since_critical);
+
+    let mut taps = Table::new(&["tap", "critical", "multiplier", "gem", "reward"]);
+    for tap in &expected.taps {
+        taps.row(vec![
+            tap.index.to_string(),
+            if tap.is_critical { "yes" } else { "no" }.to_string(),
+            tap.multiplier.to_string(),
+            optional(tap.gem.map(|gem| format!("{:?}", gem))),
+            mine(tap.reward),
+        ]);
+    }
+
+    Ok(Report::new(&vector)?.table(summary).table(taps))
+}
+
+#[derive(Serialize)]
+pub struct VectorCheck {
+    pub name: String,
+    /// Why the vector does not replay, `None` if it does
+    pub error: Option<String>,
+}
+
+/// `vectors check`: replay a vector file, failing on any drift
+pub fn vectors_check_command(args: &mut Args) -> Result<Report, CliError> {
+    let file = VectorFile::from_json(&std::fs::read_to_string(args.required("file")?)?)?;
+    let checks: Vec<VectorCheck> = file
+        .vectors
+        .iter()
+        .map(|vector| VectorCheck {
+            name: vector.name.clone(),
+            error: vector.check().err().map(|err| err.to_string()),
+        })
+        .collect();
+
+    let mut table = Table::new(&["vector", "result"]);
+    for check in &checks {
+        table.row(vec![
+            check.name.clone(),
+            check.error.clone().unwrap_or_else(|| "ok".to_string()),
+        ]);
+    }
+
+    let mut report = Report::new(&checks)?.table(table);
+    report.failed = checks.iter().any(|check| check.error.is_some());
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::Format;
+    use primitive_types::U256;
+
+    fn word(value: u64) -> String {
+        format!("{:#x}", U256::from(value))
+    }
+
+    const PLAYER: &str = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+
+    fn args(line: &str) -> Args {
+        Args::new(line.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn test_commit_then_verify_reveal() {
+        let config = GameConfig::default();
+        let line = format!(
+            "--address {} --taps 5 --block 7 --secret {} --nonce {}",
+            PLAYER,
+            word(11),
+            word(22)
+        );
+        let mut commit_args = args(&line);
+        let bundle = commit_command(&mut commit_args, &config).unwrap();
+        commit_args.finish().unwrap();
+        let hash = bundle.json()["hash"].as_str().unwrap().to_string();
+        // Tables show the full hash, not an abbreviated debug form
+        assert_eq!(hash.len(), 66);
+        assert!(bundle.render(Format::Table).contains(&hash));
+
+        let line = format!(
+            "--address {} --secret {} --nonce {} --taps 5 --block-hash 0x{} --commitment {} --power 9",
+            PLAYER,
+            word(11),
+            word(22),
+            "ab".repeat(32),
+            hash
+        );
+        let mut reveal_args = args(&line);
+        let report = verify_reveal_command(&mut reveal_args, &config).unwrap();
+        reveal_args.finish().unwrap();
+        assert!(!report.failed);
+        assert_eq!(report.json()["matches"], true);
+        assert_eq!(
+            report.json()["outcome"]["execution"]["outcomes"]
+                .as_array()
+                .unwrap()
+                .len(),
+            5
+        );
+
+        // Any other opening does not match the commitment
+        let line = line.replace(&word(22), &word(23));
+        let report = verify_reveal_command(&mut args(&line), &config).unwrap();
+        assert!(report.failed);
+    }
+
+    #[test]
+    fn test_commit_checks_tap_count() {
+        let config = GameConfig::default();
+        let line = format!("--address {} --taps 21", PLAYER);
+        assert!(matches!(
+            commit_command(&mut args(&line), &config),
+            Err(CliError::Failed(_))
+        ));
+
+        let line = format!("--address {} --secret 0x1", PLAYER);
+        assert!(matches!(
+            commit_command(&mut args(&line), &config),
+            Err(CliError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn test_ev_matches_distribution() {
+        let config = GameConfig::default();
+        let report = ev_command(&mut args("--taps 1"), &config).unwrap();
+        let json = report.json();
+
+        // Zero power still earns the base reward of 10 MINE
+        assert_eq!(json["base_tap_reward"], "10000000000000000000");
+        let mean = json["next_tap"]["mean"].as_f64().unwrap();
+        assert!((mean - 25.75).abs() < 1e-9);
+        assert!((json["session"]["expected_reward"].as_f64().unwrap() - mean).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_simulations_run() {
+        let config = GameConfig::default();
+        let report = simulate_sessions_command(
+            &mut args("--sessions 20 --taps 30 --seed 0x2a --fast"),
+            &config,
+        )
+        .unwrap();
+        assert_eq!(report.json()["params"]["sessions"], 20);
+        assert_eq!(report.json()["params"]["source"], "fast");
+
+        let report = simulate_economy_command(
+            &mut args("--level 0 --target 3 --balance 150 --miners 4"),
+            &config,
+        )
+        .unwrap();
+        assert_eq!(report.json()["steps"].as_array().unwrap().len(), 3);
+        assert_eq!(report.json()["total_cost"], "600000000000000000000");
+    }
+
+    #[test]
+    fn test_generated_vectors_check() {
+        let report = vectors_generate_command(&mut args("--seed 3 --count 4")).unwrap();
+        let mut json = report.json().clone();
+        assert_eq!(json["vectors"].as_array().unwrap().len(), 4);
+
+        let file =
+            std::env::temp_dir().join(format!("tapforge-vectors-{}.json", std::process::id()));
+        std::fs::write(&file, json.to_string()).unwrap();
+        let line = format!("--file {}", file.display());
+        assert!(!vectors_check_command(&mut args(&line)).unwrap().failed);
+
+        json["vectors"][2]["expected"]["total_reward"] = "1".into();
+        std::fs::write(&file, json.to_string()).unwrap();
+        let report = vectors_check_command(&mut args(&line)).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        assert!(report.failed);
+        assert_eq!(report.json()[1]["error"], serde_json::Value::Null);
+        assert!(report.json()[2]["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("total_reward"));
+    }
+
+    #[test]
+    fn test_vectors_replay_with_a_block_hash() {
+        let report = vectors_generate_command(&mut args("--seed 3 --count 2")).unwrap();
+        let file =
+            std::env::temp_dir().join(format!("tapforge-replay-{}.json", std::process::id()));
+        std::fs::write(&file, report.json().to_string()).unwrap();
+
+        let line = format!("--file {} --name random-1", file.display());
+        let pinned = vectors_replay_command(&mut args(&line)).unwrap();
+        assert_eq!(*pinned.json(), report.json()["vectors"][1]);
+
+        let hash = format!("{:#x}", H256([0x5a; 32]));
+        let line = format!(
+            "--file {} --name random-1 --block-hash {}",
+            file.display(),
+            hash
+        );
+        let replayed = vectors_replay_command(&mut args(&line)).unwrap();
+        assert_eq!(replayed.json()["input"]["seed_inputs"]["block_hash"], hash);
+        assert_ne!(
+            replayed.json()["expected"]["seed"],
+            pinned.json()["expected"]["seed"]
+        );
+
+        let line = format!("--file {} --name random-9", file.display());
+        let missing = vectors_replay_command(&mut args(&line));
+        std::fs::remove_file(&file).unwrap();
+        assert!(matches!(missing, Err(CliError::Usage(_))));
+    }
+}
@@ -0,0 +1,112 @@
+//! `tapforge`: the game mechanics from the command line.
+//!
+//! Runs the same code the WASM module exports, natively, for simulations,
+//! commit-reveal tooling and checking revealed taps against the contract.
+
+mod args;
+mod commands;
+mod output;
+
+use std::process::ExitCode;
+
+use tap_forge_wasm::config::GameConfig;
+
+use args::{usage, Args, CliError};
+use output::{Format, Report};
+
+const USAGE: &str = "\
+usage: tapforge <command> [options] [--config FILE] [--format table|json]
+
+commands:
+  simulate sessions  Monte Carlo over seeded sessions
+                     --sessions N --taps N --seed WORD --taps-since-critical N
+                     --power N --commit-reveal --bins N --fast
+  simulate economy   upgrade plan paid for by tap rewards
+                     --level N --target N --balance MINE --miners P1,P2,...
+                     --taps-per-minute X --commit-reveal
+  commit             commitment bundle for commitTap, random unless given
+                     --address ADDR --taps N --block N [--secret WORD --nonce WORD]
+  verify-reveal      replay revealTap from the opening and the block hash
+                     --address ADDR --secret WORD --nonce WORD --taps N
+                     --block-hash HASH [--commitment HASH]
+                     [--player FILE] [--power N] [--taps-since-critical N]
+  ev                 exact expected rewards, no sampling
+                     --power N --taps-since-critical N --taps N --commit-reveal
+  vectors generate   random golden vectors for the contract parity tests
+                     --seed N --count N
+  vectors check      replay a golden vector file
+                     --file FILE
+  vectors replay     one vector, with the block hash a chain picked if given
+                     --file FILE --name NAME [--block-hash HASH]
+
+--config reads a GameConfig JSON file, the contract defaults otherwise.
+verify-reveal exits with 1 when the opening does not match --commitment,
+vectors check when any vector drifts.
+";
+
+fn config(args: &mut Args) -> Result<GameConfig, CliError> {
+    let Some(file) = args.value("config")? else {
+        return Ok(GameConfig::default());
+    };
+    let config = GameConfig::from_json(&std::fs::read_to_string(file)?)?;
+    config.validate()?;
+    Ok(config)
+}
+
+fn run(mut args: Args) -> Result<(Report, Format), CliError> {
+    let command = args.positional().ok_or_else(|| usage("missing command"))?;
+    let config = config(&mut args)?;
+    let format = match args.value("format")? {
+        Some(format) => format
+            .parse()
+            .map_err(|_| usage(format!("unknown format `{}`", format)))?,
+        None => Format::Table,
+    };
+
+    let report = match command.as_str() {
+        "simulate" => match args.positional().as_deref() {
+            Some("sessions") => commands::simulate_sessions_command(&mut args, &config)?,
+            Some("economy") => commands::simulate_economy_command(&mut args, &config)?,
+            _ => return Err(usage("simulate needs `sessions` or `economy`")),
+        },
+        "commit" => commands::commit_command(&mut args, &config)?,
+        "verify-reveal" => commands::verify_reveal_command(&mut args, &config)?,
+        "ev" => commands::ev_command(&mut args, &config)?,
+        "vectors" => match args.positional().as_deref() {
+            Some("generate") => commands::vectors_generate_command(&mut args)?,
+            Some("check") => commands::vectors_check_command(&mut args)?,
+            Some("replay") => commands::vectors_replay_command(&mut args)?,
+            _ => return Err(usage("vectors needs `generate`, `check` or `replay`")),
+        },
+        _ => return Err(usage(format!("unknown command `{}`", command))),
+    };
+    args.finish()?;
+    Ok((report, format))
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        print!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    match run(Args::new(args)) {
+        Ok((report, format)) => {
+            print!("{}", report.render(format));
+            if report.failed {
+                ExitCode::FAILURE
+            } else {
+                ExitCode::SUCCESS
+            }
+        }
+        Err(CliError::Usage(message)) => {
+            eprintln!("tapforge: {}\n\n{}", message, USAGE);
+            ExitCode::from(2)
+        }
+        Err(CliError::Failed(err)) => {
+            eprintln!("tapforge: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! JSON and plain-text table rendering.
+//!
+//! JSON is the library's own serde output, amounts in wei as decimal
+//! strings. Tables are for reading: amounts in MINE, floats rounded.
+
+use serde::Serialize;
+use tap_forge_wasm::amount::TokenAmount;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Json,
+}
+
+impl std::str::FromStr for Format {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Rows under optional headers, columns padded to the widest cell
+#[derive(Debug, Default)]
+pub struct Table {
+    title: Option<String>,
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(headers: &[&str]) -> Self {
+        Table {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            ..Table::default()
+        }
+    }
+
+    /// Two columns of label and value, no headers
+    pub fn fields() -> Self {
+        Table::default()
+    }
+
+    pub fn titled(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    pub fn row(&mut self, cells: Vec<String>) -> &mut Self {
+        self.rows.push(cells);
+        self
+    }
+
+    pub fn field(&mut self, label: &str, value: impl ToString) -> &mut Self {
+        self.row(vec![label.to_string(), value.to_string()])
+    }
+
+    pub fn render(&self) -> String {
+        let columns = self
+            .rows
+            .iter()
+            .map(Vec::len)
+            .chain([self.headers.len()])
+            .max()
+            .unwrap_or_default();
+        let mut widths = vec![0; columns];
+        for row in self.rows.iter().chain([&self.headers]) {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let line = |cells: &[String]| {
+            let padded: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+            padded.join("  ").trim_end().to_string() + "\n"
+        };
+
+        let mut out = String::new();
+        if let Some(title) = &self.title {
+            out += &format!("{}\n", title);
+        }
+        if !self.headers.is_empty() {
+            out += &line(&self.headers);
+            let rule: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+            out += &line(&rule);
+        }
+        for row in &self.rows {
+            out += &line(row);
+        }
+        out
+    }
+}
+
+/// What a command prints: its JSON value and the tables showing it
+pub struct Report {
+    json: serde_json::Value,
+    tables: Vec<Table>,
+    /// A check failed, the process exits non-zero after printing
+    pub failed: bool,
+}
+
+impl Report {
+    pub fn new<T: Serialize>(value: &T) -> Result<Self, serde_json::Error> {
+        Ok(Report {
+            json: serde_json::to_value(value)?,
+            tables: Vec::new(),
+            failed: false,
+        })
+    }
+
+    #[cfg(test)]
+    pub fn json(&self) -> &serde_json::Value {
+        &self.json
+    }
+
+    pub fn table(mut self, table: Table) -> Self {
+        self.tables.push(table);
+        self
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Json => serde_json::to_string_pretty(&self.json).unwrap_or_default() + "\n",
+            Format::Table => self
+                .tables
+                .iter()
+                .map(Table::render)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+/// Exact amount in MINE, without trailing zeros
+pub fn mine(amount: TokenAmount) -> String {
+    let wei = format!("{:0>19}", amount.to_string());
+    let (whole, fraction) = wei.split_at(wei.len() - 18);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+pub fn percent(probability: f64) -> String {
+    format!("{:.4}%", probability * 100.0)
+}
+
+pub fn float(value: f64) -> String {
+    format!("{:.4}", value)
+}
+
+pub fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mine() {
+        assert_eq!(mine(TokenAmount::ZERO), "0");
+        assert_eq!(mine(TokenAmount::from(1)), "0.000000000000000001");
+        assert_eq!(mine(TokenAmount::from_tokens(2_020)), "2020");
+        assert_eq!(mine(TokenAmount::from(12_500_000_000_000_000_000)), "12.5");
+    }
+
+    #[test]
+    fn test_table_columns_line_up() {
+        let mut table = Table::new(&["reward", "probability"]);
+        table.row(vec!["10".into(), "90%".into()]);
+        table.row(vec!["2020".into(), "0.25%".into()]);
+        assert_eq!(
+            table.render(),
+            "reward  probability\n------  -----------\n10      90%\n2020    0.25%\n"
+        );
+
+        let mut fields = Table::fields().titled("Session");
+        fields.field("taps", 100).field("mean reward", "25.75");
+        assert_eq!(
+            fields.render(),
+            "Session\ntaps         100\nmean reward  25.75\n"
+        );
+    }
+}
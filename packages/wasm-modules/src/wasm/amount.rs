@@ -0,0 +1,41 @@
+//! `TokenAmount` to and from JS `BigInt`.
+
+use js_sys::BigInt;
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::amount::TokenAmount;
+use crate::error::GameError;
+
+/// `TokenAmount` conversions, a trait since the type lives in `tap-forge-core`
+pub trait AmountJs: Sized {
+    /// Convert to a JS `BigInt`
+    fn to_js(&self) -> JsValue;
+
+    /// Accept a JS `BigInt`, a non-negative integer number or a numeric string
+    fn from_js(value: &JsValue) -> Result<Self, GameError>;
+}
+
+impl AmountJs for TokenAmount {
+    fn to_js(&self) -> JsValue {
+        JsValue::bigint_from_str(&self.to_string())
+    }
+
+    fn from_js(value: &JsValue) -> Result<Self, GameError> {
+        if value.is_bigint() {
+            let digits = BigInt::unchecked_from_js_ref(value)
+                .to_string(10)
+                .map_err(|_| GameError::InvalidAmount)?;
+            return Self::parse(&String::from(digits));
+        }
+        if let Some(number) = value.as_f64() {
+            if number >= 0.0 && number.fract() == 0.0 && number <= 9_007_199_254_740_991.0 {
+                return Ok(TokenAmount::from(number as u64));
+            }
+            return Err(GameError::InvalidAmount);
+        }
+        match value.as_string() {
+            Some(text) => Self::parse(&text),
+            None => Err(GameError::InvalidAmount),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! The `TapBatcher` class.
+
+use wasm_bindgen::prelude::*;
+
+use crate::batcher::TapBatcher;
+use crate::power::TapPath;
+
+use super::bindings::to_js;
+use super::game_config_from_js;
+
+/// [`TapBatcher`] exported as the `TapBatcher` class
+#[wasm_bindgen(js_name = TapBatcher)]
+pub struct JsTapBatcher(TapBatcher);
+
+#[wasm_bindgen(js_class = TapBatcher)]
+impl JsTapBatcher {
+    /// Batcher for `commitTap` calls when `commit_reveal`, `tapMine` otherwise
+    #[wasm_bindgen(constructor)]
+    pub fn new_js(
+        commit_reveal: bool,
+        #[wasm_bindgen(unchecked_param_type = "Partial<GameConfig> | undefined")] config: JsValue,
+    ) -> Result<JsTapBatcher, JsError> {
+        let path = if commit_reveal {
+            TapPath::CommitReveal
+        } else {
+            TapPath::TapMine
+        };
+        Ok(JsTapBatcher(TapBatcher::new(
+            game_config_from_js(config)?,
+            path,
+        )))
+    }
+
+    #[wasm_bindgen(getter, js_name = queued)]
+    pub fn queued_js(&self) -> u64 {
+        self.0.queued()
+    }
+
+    #[wasm_bindgen(js_name = setHorizonBlocks)]
+    pub fn set_horizon_blocks_js(&mut self, horizon_blocks: u64) {
+        self.0.set_horizon_blocks(horizon_blocks);
+    }
+
+    #[wasm_bindgen(js_name = push)]
+    pub fn push_js(&mut self, taps: u64) -> Result<(), JsError> {
+        Ok(self.0.push(taps)?)
+    }
+
+    #[wasm_bindgen(js_name = recordBlockTaps)]
+    pub fn record_block_taps_js(&mut self, block_number: u64, taps: u64) {
+        self.0.record_block_taps(block_number, taps);
+    }
+
+    /// Reveal block of the outstanding commitment, `undefined` when none is pending
+    #[wasm_bindgen(getter, js_name = pendingReveal)]
+    pub fn pending_reveal_js(&self) -> Option<u64> {
+        self.0.pending_reveal()
+    }
+
+    #[wasm_bindgen(js_name = recordCommit)]
+    pub fn record_commit_js(&mut self, commit_block: u64) {
+        self.0.record_commit(commit_block);
+    }
+
+    /// `BatchPlan` object for the current queue
+    #[wasm_bindgen(js_name = plan, unchecked_return_type = "BatchPlan")]
+    pub fn plan_js(&self, current_block: u64) -> Result<JsValue, JsError> {
+        to_js(&self.0.plan(current_block))
+    }
+
+    /// Array of `TapCall` objects to send now
+    #[wasm_bindgen(js_name = drain, unchecked_return_type = "TapCall[]")]
+    pub fn drain_js(&mut self, current_block: u64) -> Result<JsValue, JsError> {
+        to_js(&self.0.drain(current_block))
+    }
+
+    #[wasm_bindgen(js_name = dropOverflow)]
+    pub fn drop_overflow_js(&mut self, current_block: u64) -> u64 {
+        self.0.drop_overflow(current_block)
+    }
+}
@@ -0,0 +1,412 @@
+//! The JS boundary: conversions and TypeScript declarations.
+//!
+//! Exported results go through `to_js` and inputs through `from_js`. On that
+//! path `u64` and `u128` fields become `BigInt` and a missing optional becomes
+//! `null`, and types carrying token amounts go through their DTO in
+//! [`super::dto`], which makes the amounts wei `BigInt` instead of decimal
+//! strings. Nothing above 2^53 is rounded. JSON (`exportState`, `seal`,
+//! configs) keeps its decimal strings. Hashes, seeds, secrets and nonces stay `0x` hex
+//! strings either way.
+//!
+//! The declarations below describe exactly those shapes. `TapResult`,
+//! `PlayerStats`, `GameParams` and `GemType` come from `@tap-forge/shared`.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_wasm_bindgen::Serializer;
+use wasm_bindgen::prelude::*;
+
+const SERIALIZER: Serializer = Serializer::new()
+    .serialize_large_number_types_as_bigints(true)
+    .serialize_missing_as_null(true);
+
+/// Convert an exported result, 64-bit and larger numbers as `BigInt`
+pub(crate) fn to_js<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, JsError> {
+    Ok(value.serialize(&SERIALIZER)?)
+}
+
+/// Read an argument object, numbers accepted as `BigInt` or safe integers
+pub(crate) fn from_js<T: DeserializeOwned>(value: JsValue) -> Result<T, JsError> {
+    Ok(serde_wasm_bindgen::from_value(value)?)
+}
+
+/// Declarations appended to the generated `.d.ts`
+pub const TS_DECLARATIONS: &str = r#"
+import type { Address, GameParams, GemType, PlayerStats, Rarity, TapResult } from "@tap-forge/shared";
+export type { Address, GameParams, GemType, PlayerStats, Rarity, TapResult };
+
+/** Wei amount argument: a `BigInt`, a safe integer or a decimal or `0x` string */
+export type Amount = bigint | number | string;
+export type Hex = `0x${string}`;
+export type TapPath = "commit_reveal" | "tap_mine";
+export type SourceKind = "keccak" | "fast";
+export type GameErrorCode =
+  | "INVALID_ADDRESS" | "INVALID_TAP_COUNT" | "PENDING_COMMITMENT" | "NO_COMMITMENT"
+  | "ALREADY_REVEALED" | "TOO_EARLY" | "BLOCK_HASH_NOT_AVAILABLE" | "TOO_LATE"
+  | "INVALID_REVEAL" | "TOO_MANY_TAPS" | "TOTAL_POWER_OVERFLOW" | "NO_PENDING_REWARDS"
+  | "DAILY_LIMIT_EXCEEDED" | "NOT_MINER_OWNER" | "MAX_MINERS_REACHED" | "ALREADY_REGISTERED"
+  | "MINER_NOT_REGISTERED" | "MAX_LEVEL_REACHED" | "INSUFFICIENT_BALANCE"
+  | "INSUFFICIENT_ALLOWANCE" | "INVALID_LIMIT" | "LIMIT_TOO_HIGH" | "INVALID_GEM"
+  | "INVALID_CHANCE" | "BONUS_TOO_HIGH" | "INVALID_WEIGHTS_LENGTH" | "WEIGHTS_MUST_SUM_TO_100"
+  | "PAUSED" | "NONEXISTENT_TOKEN" | "ARITHMETIC_OVERFLOW" | "NFT_MAX_SUPPLY_REACHED"
+  | "NFT_NAME_TOO_LONG" | "NFT_INVALID_COUNT" | "NFT_ARRAYS_MISMATCH" | "NFT_MAX_SUPPLY_EXCEEDED"
+  | "NFT_TOKEN_DOES_NOT_EXIST" | "NFT_POWER_OVERFLOW" | "NFT_POWER_TOO_HIGH"
+  | "NFT_NOT_THE_OWNER" | "TOKEN_AMOUNT_EXCEEDS_MAX" | "TOKEN_MAX_SUPPLY_EXCEEDED"
+  | "TOKEN_EMPTY_ARRAYS" | "TOKEN_ARRAYS_LENGTH_MISMATCH" | "TOKEN_ARITHMETIC_OVERFLOW"
+  | "INVALID_AMOUNT" | "INVALID_SEED" | "INVALID_HASH" | "INVALID_SECRET" | "INVALID_NONCE"
+  | "INVALID_LOG_DATA";
+
+export interface GemReward {
+  bonus: bigint;
+  drop_chance: bigint;
+}
+
+export interface GameConfig {
+  base_reward: bigint;
+  max_taps_per_call: number;
+  max_taps_per_block: bigint;
+  upgrade_cost_multiplier: bigint;
+  pity_threshold: bigint;
+  pity_increment: bigint;
+  max_pity_bonus: bigint;
+  critical_base_chance: bigint;
+  max_level: number;
+  max_registered_miners: bigint;
+  commit_reveal_blocks: bigint;
+  max_reveal_delay: bigint;
+  max_daily_mint: bigint;
+  daily_mint_limit: bigint;
+  commit_reveal_bonus_percent: bigint;
+  critical_multipliers: [number, number, number, number];
+  critical_weights: [bigint, bigint, bigint, bigint];
+  gem_drop_chance: bigint;
+  gem_rewards: { RUBY: GemReward; SAPPHIRE: GemReward; DIAMOND: GemReward };
+}
+
+export interface RewardPrediction extends TapResult {
+  baseReward: bigint;
+}
+
+export type PlayerStatsSummary = Omit<PlayerStats, "miners">;
+
+export interface PlayerData {
+  level: number;
+  total_power: bigint;
+  pending_rewards: bigint;
+  total_mined: bigint;
+  total_taps: bigint;
+  critical_hits: bigint;
+  last_tap_block: bigint;
+  taps_since_critical: bigint;
+  registered_miners: bigint[];
+}
+
+export interface MinerInfo {
+  token_id: bigint;
+  owner: Address;
+  power: bigint;
+}
+
+export interface PowerBreakdown {
+  counted_miners: bigint[];
+  skipped_miners: bigint[];
+  miner_power: bigint;
+  level: number;
+  total_power: bigint;
+  base_tap_reward: bigint;
+}
+
+export interface GemProbabilities {
+  any: number;
+  ruby: number;
+  sapphire: number;
+  diamond: number;
+}
+
+export interface GemDropProbabilities {
+  per_tap: GemProbabilities;
+  batch: GemProbabilities;
+}
+
+export interface TapOutcome {
+  index: number;
+  seed: Hex;
+  critical_chance: bigint;
+  is_critical: boolean;
+  multiplier: number;
+  gem: GemType | null;
+  gem_bonus: bigint;
+  reward: bigint;
+}
+
+export interface TapExecution {
+  outcomes: TapOutcome[];
+  total_reward: bigint;
+  has_critical: boolean;
+  critical_hits: bigint;
+  taps_since_critical: bigint;
+  final_seed: Hex;
+}
+
+export interface RevealOutcome {
+  seed: Hex;
+  execution: TapExecution;
+  player: PlayerData;
+}
+
+export interface CommitmentBundle {
+  player: Address;
+  secret: Hex;
+  nonce: Hex;
+  taps: number;
+  hash: Hex;
+  created_at_block: bigint;
+  committed_at_block: bigint | null;
+}
+
+export interface RecoveredCommitment {
+  index: bigint;
+  bundle: CommitmentBundle;
+}
+
+export interface Commitment {
+  hash: Hex;
+  block_number: bigint;
+  taps: number;
+  revealed: boolean;
+}
+
+export type CommitState = "idle" | "committed" | "revealable" | "expired" | "revealed";
+export type CommitAction = "commit" | "reveal" | "tap_mine";
+
+export interface CommitStatus {
+  state: CommitState;
+  commitment: Commitment | null;
+  reveal_from: bigint | null;
+  reveal_until: bigint | null;
+  blocks_until_revealable: bigint;
+  blocks_remaining: bigint;
+  allowed_actions: CommitAction[];
+}
+
+export interface TapCall {
+  path: TapPath;
+  taps: number;
+  send_block: bigint;
+  execute_block: bigint;
+}
+
+export interface BatchPlan {
+  calls: TapCall[];
+  ready: bigint;
+  deferred: bigint;
+  dropped: bigint;
+}
+
+export type WithdrawAvailability =
+  | { status: "now" }
+  | { status: "next_day"; at_timestamp: bigint; wait_seconds: bigint }
+  | { status: "never" };
+
+export interface WithdrawRequest {
+  player: Address;
+  amount: Amount;
+  timestamp: bigint | number;
+}
+
+export interface WithdrawOutcome {
+  player: Address;
+  amount: bigint;
+  error: GameErrorCode | null;
+  todays_minted: bigint;
+}
+
+export interface ContentionReport {
+  outcomes: WithdrawOutcome[];
+  minted: bigint;
+  reverted: bigint;
+  reverted_amount: bigint;
+}
+
+export interface RewardOutcome {
+  is_critical: boolean;
+  multiplier: number;
+  gem: GemType | null;
+  reward: bigint;
+  weight: bigint;
+  probability: number;
+}
+
+export interface TapRewardDistribution {
+  taps_since_critical: bigint;
+  critical_chance: bigint;
+  base_tap_reward: bigint;
+  outcomes: RewardOutcome[];
+  expected_reward: bigint;
+  mean: number;
+  variance: number;
+  standard_deviation: number;
+}
+
+export interface TapsUntilCritical {
+  probabilities: number[];
+  beyond: number;
+  expected_taps: number | null;
+}
+
+export interface SessionForecast {
+  taps: number;
+  expected_criticals: number;
+  expected_reward: number;
+  final_states: number[];
+}
+
+export interface MonteCarloParams {
+  sessions: number;
+  taps: number;
+  seed: Hex;
+  taps_since_critical: bigint;
+  total_power: bigint;
+  path: TapPath;
+  histogram_bins: number;
+  source: SourceKind;
+}
+
+export interface GemCounts {
+  ruby: bigint;
+  sapphire: bigint;
+  diamond: bigint;
+}
+
+export interface Percentiles {
+  min: number;
+  p5: number;
+  p50: number;
+  p95: number;
+  p99: number;
+  max: number;
+}
+
+export interface Histogram {
+  min: number;
+  max: number;
+  bin_width: number;
+  counts: bigint[];
+}
+
+export interface MonteCarloReport {
+  params: MonteCarloParams;
+  mean_reward: number;
+  reward: Percentiles;
+  criticals: Percentiles;
+  pity_resets: Percentiles;
+  longest_dry_streak: Percentiles;
+  gems: GemCounts;
+  max_tap_reward: bigint;
+  worst_dry_streak: bigint;
+  reward_histogram: Histogram;
+}
+
+export interface UpgradeStep {
+  level: number;
+  cost: bigint;
+  cumulative_cost: bigint;
+  total_power: bigint;
+  base_tap_reward: bigint;
+  expected_reward_per_tap: number;
+  extra_reward_per_tap: number;
+  taps_to_afford: bigint | null;
+  seconds_to_afford: number | null;
+  payback_taps: number | null;
+}
+
+export interface UpgradePlan {
+  current_level: number;
+  target_level: number;
+  total_power: bigint;
+  expected_reward_per_tap: number;
+  total_cost: bigint;
+  steps: UpgradeStep[];
+}
+
+export type EventKind =
+  | "Tapped" | "GemFound" | "Withdrawn" | "MinerRegistered" | "MinerUnregistered"
+  | "PlayerUpgraded" | "CommitmentMade" | "CommitmentRevealed" | "EmergencyPause"
+  | "DailyLimitUpdated" | "MinerMinted" | "MinerUpgraded" | "MinerRenamed"
+  | "TokensMinted" | "TokensBurned" | "Transfer";
+
+export interface EventTopic {
+  event: EventKind;
+  signature: string;
+  topic: Hex;
+}
+
+export type ContractEvent =
+  | { event: "Tapped"; player: Address; taps: bigint; reward: bigint; critical: boolean }
+  | { event: "GemFound"; player: Address; gem_type: GemType; bonus: bigint }
+  | { event: "Withdrawn"; player: Address; amount: bigint }
+  | { event: "MinerRegistered"; player: Address; token_id: bigint }
+  | { event: "MinerUnregistered"; player: Address; token_id: bigint }
+  | { event: "PlayerUpgraded"; player: Address; new_level: number; cost: bigint }
+  | { event: "CommitmentMade"; player: Address; commitment: Hex }
+  | { event: "CommitmentRevealed"; player: Address; reward: bigint }
+  | { event: "EmergencyPause"; caller: Address }
+  | { event: "DailyLimitUpdated"; new_limit: bigint }
+  | { event: "MinerMinted"; to: Address; token_id: bigint; rarity: Rarity; power: bigint }
+  | { event: "MinerUpgraded"; token_id: bigint; new_power: bigint }
+  | { event: "MinerRenamed"; token_id: bigint; new_name: string }
+  | { event: "TokensMinted"; to: Address; amount: bigint }
+  | { event: "TokensBurned"; from: Address; amount: bigint }
+  | { event: "Erc20Transfer"; from: Address; to: Address; value: bigint }
+  | { event: "Erc721Transfer"; from: Address; to: Address; token_id: bigint };
+"#;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_SECTION: &'static str = TS_DECLARATIONS;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::GameError;
+
+    #[test]
+    fn test_declared_error_codes() {
+        for error in GameError::ALL {
+            assert!(
+                TS_DECLARATIONS.contains(&format!("\"{}\"", error.code())),
+                "{} missing from GameErrorCode",
+                error.code()
+            );
+        }
+    }
+
+    #[test]
+    fn test_declared_events() {
+        for kind in crate::events::EventKind::ALL {
+            assert!(
+                TS_DECLARATIONS.contains(&format!("\"{:?}\"", kind)),
+                "{:?} missing from EventKind",
+                kind
+            );
+        }
+    }
+
+    #[test]
+    fn test_json_keeps_strings() {
+        let breakdown = crate::power::PowerBreakdown {
+            counted_miners: vec![1],
+            skipped_miners: vec![],
+            miner_power: primitive_types::U256::from(255),
+            level: 0,
+            total_power: 255,
+            base_tap_reward: crate::amount::TokenAmount::from_tokens(1),
+        };
+        let json = serde_json::to_value(&breakdown).unwrap();
+        assert_eq!(json["miner_power"], "0xff");
+        assert_eq!(json["base_tap_reward"], "1000000000000000000");
+        assert_eq!(
+            serde_json::from_value::<crate::power::PowerBreakdown>(json).unwrap(),
+            breakdown
+        );
+    }
+}
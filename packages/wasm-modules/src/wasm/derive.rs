@@ -0,0 +1,73 @@
+//! `secretDerivationMessage` and the `SecretDeriver` class.
+
+use wasm_bindgen::prelude::*;
+
+use crate::derive::{secret_message, InvalidSignatureError, SecretDeriver, SIGNATURE_LEN};
+use crate::tracker::Commitment;
+
+use super::bindings::{from_js, to_js};
+use super::game_config_from_js;
+
+fn parse_signature(signature: &str) -> Option<[u8; SIGNATURE_LEN]> {
+    let digits = signature.strip_prefix("0x")?;
+    let mut bytes = [0u8; SIGNATURE_LEN];
+    hex::decode_to_slice(digits, &mut bytes).ok()?;
+    Some(bytes)
+}
+
+/// Message to pass to `personal_sign` before deriving commit secrets
+#[wasm_bindgen]
+pub fn secret_derivation_message(
+    domain: &str,
+    chain_id: u64,
+    user_address: &str,
+) -> Result<String, JsError> {
+    Ok(secret_message(domain, chain_id, &user_address.parse()?))
+}
+
+/// [`SecretDeriver`] exported as the `SecretDeriver` class
+#[wasm_bindgen(js_name = SecretDeriver)]
+pub struct JsSecretDeriver(SecretDeriver);
+
+#[wasm_bindgen(js_class = SecretDeriver)]
+impl JsSecretDeriver {
+    /// `signature` is the hex `personal_sign` result over `secretDerivationMessage`
+    #[wasm_bindgen(constructor)]
+    pub fn new_js(
+        signature: &str,
+        chain_id: u64,
+        user_address: &str,
+    ) -> Result<JsSecretDeriver, JsError> {
+        let signature = parse_signature(signature).ok_or(InvalidSignatureError)?;
+        Ok(JsSecretDeriver(SecretDeriver::new(
+            &signature,
+            chain_id,
+            user_address.parse()?,
+        )?))
+    }
+
+    /// `CommitmentBundle` object for commitment number `index`
+    #[wasm_bindgen(js_name = commitment, unchecked_return_type = "CommitmentBundle")]
+    pub fn commitment_js(
+        &self,
+        index: u64,
+        taps: u16,
+        block_number: u64,
+        #[wasm_bindgen(unchecked_param_type = "Partial<GameConfig> | undefined")] config: JsValue,
+    ) -> Result<JsValue, JsError> {
+        game_config_from_js(config)?.check_tap_count(taps)?;
+        to_js(&self.0.bundle(index, taps, block_number))
+    }
+
+    /// Find the `RecoveredCommitment` behind a `getCommitment` result, `null` if none
+    #[wasm_bindgen(js_name = recover, unchecked_return_type = "RecoveredCommitment | null")]
+    pub fn recover_js(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "Commitment")] commitment: JsValue,
+        start_index: u64,
+        max_candidates: u64,
+    ) -> Result<JsValue, JsError> {
+        let commitment: Commitment = from_js(commitment)?;
+        to_js(&self.0.recover(&commitment, start_index, max_candidates))
+    }
+}
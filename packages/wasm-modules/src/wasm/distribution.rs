@@ -0,0 +1,33 @@
+//! The `tap_reward_distribution` export.
+
+use wasm_bindgen::prelude::*;
+
+use crate::distribution::tap_reward_distribution;
+use crate::power::{base_tap_reward, TapPath};
+
+use super::bindings::to_js;
+use super::dto::Js;
+use super::game_config_from_js;
+
+/// `TapRewardDistribution` object of the next tap for `total_power` (a `BigInt`)
+#[wasm_bindgen(js_name = tap_reward_distribution, unchecked_return_type = "TapRewardDistribution")]
+pub fn tap_reward_distribution_js(
+    taps_since_critical: u64,
+    total_power: u128,
+    commit_reveal: bool,
+    #[wasm_bindgen(unchecked_param_type = "Partial<GameConfig> | undefined")] config: JsValue,
+) -> Result<JsValue, JsError> {
+    let config = game_config_from_js(config)?;
+    let path = if commit_reveal {
+        TapPath::CommitReveal
+    } else {
+        TapPath::TapMine
+    };
+
+    let distribution = tap_reward_distribution(
+        &config,
+        taps_since_critical,
+        base_tap_reward(&config, total_power, path)?,
+    )?;
+    to_js(&Js(&distribution))
+}
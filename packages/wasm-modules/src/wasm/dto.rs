@@ -0,0 +1,528 @@
+//! JS shapes of the core types that carry amounts.
+//!
+//! Every `…Def` below is a `#[serde(remote)]` copy of a core type. Fields
+//! marked `with = "js"` cross as `BigInt` (token amounts, numeric `U256`) or
+//! through the DTO of a nested type; every other field keeps the core serde
+//! form. Wrap a value in [`Js`] before `to_js` or `from_js` to go through its
+//! DTO. JSON never does, so it keeps the decimal strings.
+
+use primitive_types::{H256, U256};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use wasm_bindgen::JsValue;
+
+use crate::address::Address;
+use crate::amount::TokenAmount;
+use crate::config::{GameConfig, GameParams, GemReward, GemRewards};
+use crate::distribution::{RewardOutcome, TapRewardDistribution};
+use crate::error::GameError;
+use crate::events::ContractEvent;
+use crate::gems::GemType;
+use crate::limiter::{ContentionReport, WithdrawOutcome, WithdrawRequest};
+use crate::montecarlo::{GemCounts, Histogram, MonteCarloParams, MonteCarloReport, Percentiles};
+use crate::nft::Rarity;
+use crate::player::{PlayerData, PlayerStatsSummary};
+use crate::power::PowerBreakdown;
+use crate::reveal::RevealOutcome;
+use crate::taps::{RewardPrediction, TapExecution, TapOutcome};
+use crate::upgrade::{UpgradePlan, UpgradeStep};
+
+use super::amount::AmountJs;
+
+/// A value converted through its JS DTO, `Js(&value)` out and `Js(value)` in
+pub(crate) struct Js<T>(pub T);
+
+impl Serialize for Js<&TokenAmount> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde_wasm_bindgen::preserve::serialize(&self.0.to_js(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Js<TokenAmount> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value: JsValue = serde_wasm_bindgen::preserve::deserialize(deserializer)?;
+        TokenAmount::from_js(&value)
+            .map(Js)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Numeric `U256` fields, e.g. miner power. Hashes and seeds stay hex.
+impl Serialize for Js<&U256> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Js(&TokenAmount::from(*self.0)).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Js<U256> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Js::<TokenAmount>::deserialize(deserializer).map(|Js(amount)| Js(amount.wei()))
+    }
+}
+
+/// `#[serde(with)]` for a field converted through its DTO
+mod js {
+    use super::*;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        for<'a> Js<&'a T>: Serialize,
+        S: Serializer,
+    {
+        Js(value).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        Js<T>: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Js::<T>::deserialize(deserializer).map(|Js(value)| value)
+    }
+}
+
+/// `#[serde(with)]` for a `Vec` of values converted through their DTO
+mod js_seq {
+    use super::*;
+
+    pub fn serialize<T, S>(values: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        for<'a> Js<&'a T>: Serialize,
+        S: Serializer,
+    {
+        serializer.collect_seq(values.iter().map(Js))
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        Js<T>: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let values = Vec::<Js<T>>::deserialize(deserializer)?;
+        Ok(values.into_iter().map(|Js(value)| value).collect())
+    }
+}
+
+/// `#[serde(with)]` for an `Option` of a value converted through its DTO
+mod js_option {
+    use super::*;
+
+    pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        for<'a> Js<&'a T>: Serialize,
+        S: Serializer,
+    {
+        value.as_ref().map(Js).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        Js<T>: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Option::<Js<T>>::deserialize(deserializer).map(|value| value.map(|Js(value)| value))
+    }
+}
+
+/// `Js` conversions of core types through their remote definition
+macro_rules! js_dto {
+    ($($ty:ty => $def:ident),* $(,)?) => {
+        $(
+            impl Serialize for Js<&$ty> {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    remote::$def::serialize(self.0, serializer)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for Js<$ty> {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    remote::$def::deserialize(deserializer).map(Js)
+                }
+            }
+        )*
+    };
+}
+
+js_dto! {
+    GemReward => GemRewardDef,
+    GemRewards => GemRewardsDef,
+    GameConfig => GameConfigDef,
+    GameParams => GameParamsDef,
+    PlayerData => PlayerDataDef,
+    PlayerStatsSummary => PlayerStatsSummaryDef,
+    TapOutcome => TapOutcomeDef,
+    TapExecution => TapExecutionDef,
+    RewardPrediction => RewardPredictionDef,
+    RevealOutcome => RevealOutcomeDef,
+    PowerBreakdown => PowerBreakdownDef,
+    RewardOutcome => RewardOutcomeDef,
+    TapRewardDistribution => TapRewardDistributionDef,
+    WithdrawRequest => WithdrawRequestDef,
+    WithdrawOutcome => WithdrawOutcomeDef,
+    ContentionReport => ContentionReportDef,
+    MonteCarloReport => MonteCarloReportDef,
+    UpgradeStep => UpgradeStepDef,
+    UpgradePlan => UpgradePlanDef,
+    ContractEvent => ContractEventDef,
+}
+
+/// Only ever read by the serde derives
+#[allow(dead_code)]
+mod remote {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "GemReward")]
+    pub(super) struct GemRewardDef {
+        #[serde(with = "js")]
+        bonus: TokenAmount,
+        drop_chance: u64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "GemRewards", rename_all = "SCREAMING_SNAKE_CASE")]
+    pub(super) struct GemRewardsDef {
+        #[serde(with = "js")]
+        ruby: GemReward,
+        #[serde(with = "js")]
+        sapphire: GemReward,
+        #[serde(with = "js")]
+        diamond: GemReward,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "GameConfig", default = "GameConfig::default")]
+    pub(super) struct GameConfigDef {
+        #[serde(with = "js")]
+        base_reward: TokenAmount,
+        max_taps_per_call: u16,
+        max_taps_per_block: u64,
+        #[serde(with = "js")]
+        upgrade_cost_multiplier: TokenAmount,
+        pity_threshold: u64,
+        pity_increment: u64,
+        max_pity_bonus: u64,
+        critical_base_chance: u64,
+        max_level: u32,
+        max_registered_miners: usize,
+        commit_reveal_blocks: u64,
+        max_reveal_delay: u64,
+        #[serde(with = "js")]
+        max_daily_mint: TokenAmount,
+        #[serde(with = "js")]
+        daily_mint_limit: TokenAmount,
+        commit_reveal_bonus_percent: u64,
+        critical_multipliers: [u32; 4],
+        critical_weights: [u64; 4],
+        gem_drop_chance: u64,
+        #[serde(with = "js")]
+        gem_rewards: GemRewards,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "GameParams", rename_all = "camelCase")]
+    pub(super) struct GameParamsDef {
+        #[serde(with = "js")]
+        base_reward: TokenAmount,
+        max_taps_per_call: u32,
+        max_taps_per_block: u32,
+        #[serde(with = "js")]
+        upgrade_cost_multiplier: TokenAmount,
+        pity_threshold: u32,
+        critical_weights: Vec<u32>,
+        gem_drop_rate: u32,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "PlayerData", default = "PlayerData::default")]
+    pub(super) struct PlayerDataDef {
+        level: u32,
+        total_power: u128,
+        #[serde(with = "js")]
+        pending_rewards: TokenAmount,
+        #[serde(with = "js")]
+        total_mined: TokenAmount,
+        total_taps: u64,
+        critical_hits: u64,
+        last_tap_block: u64,
+        taps_since_critical: u64,
+        registered_miners: Vec<u64>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "PlayerStatsSummary", rename_all = "camelCase")]
+    pub(super) struct PlayerStatsSummaryDef {
+        address: Address,
+        total_power: u128,
+        #[serde(with = "js")]
+        pending_rewards: TokenAmount,
+        #[serde(with = "js")]
+        total_mined: TokenAmount,
+        total_taps: u64,
+        critical_hits: u64,
+        last_tap_block: u64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "TapOutcome")]
+    pub(super) struct TapOutcomeDef {
+        index: u16,
+        seed: U256,
+        critical_chance: u64,
+        is_critical: bool,
+        multiplier: u32,
+        gem: Option<GemType>,
+        #[serde(with = "js")]
+        gem_bonus: TokenAmount,
+        #[serde(with = "js")]
+        reward: TokenAmount,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "TapExecution")]
+    pub(super) struct TapExecutionDef {
+        #[serde(with = "js_seq")]
+        outcomes: Vec<TapOutcome>,
+        #[serde(with = "js")]
+        total_reward: TokenAmount,
+        has_critical: bool,
+        critical_hits: u64,
+        taps_since_critical: u64,
+        final_seed: U256,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "RewardPrediction", rename_all = "camelCase")]
+    pub(super) struct RewardPredictionDef {
+        #[serde(with = "js")]
+        base_reward: TokenAmount,
+        #[serde(with = "js")]
+        reward: TokenAmount,
+        is_critical: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        critical_multiplier: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        gem_found: Option<GemType>,
+        #[serde(skip_serializing_if = "Option::is_none", with = "js_option")]
+        gem_bonus: Option<TokenAmount>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "RevealOutcome")]
+    pub(super) struct RevealOutcomeDef {
+        seed: U256,
+        #[serde(with = "js")]
+        execution: TapExecution,
+        #[serde(with = "js")]
+        player: PlayerData,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "PowerBreakdown")]
+    pub(super) struct PowerBreakdownDef {
+        counted_miners: Vec<u64>,
+        skipped_miners: Vec<u64>,
+        #[serde(with = "js")]
+        miner_power: U256,
+        level: u32,
+        total_power: u128,
+        #[serde(with = "js")]
+        base_tap_reward: TokenAmount,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "RewardOutcome")]
+    pub(super) struct RewardOutcomeDef {
+        is_critical: bool,
+        multiplier: u32,
+        gem: Option<GemType>,
+        #[serde(with = "js")]
+        reward: TokenAmount,
+        weight: u64,
+        probability: f64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "TapRewardDistribution")]
+    pub(super) struct TapRewardDistributionDef {
+        taps_since_critical: u64,
+        critical_chance: u64,
+        #[serde(with = "js")]
+        base_tap_reward: TokenAmount,
+        #[serde(with = "js_seq")]
+        outcomes: Vec<RewardOutcome>,
+        #[serde(with = "js")]
+        expected_reward: TokenAmount,
+        mean: f64,
+        variance: f64,
+        standard_deviation: f64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "WithdrawRequest")]
+    pub(super) struct WithdrawRequestDef {
+        player: Address,
+        #[serde(with = "js")]
+        amount: TokenAmount,
+        timestamp: u64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "WithdrawOutcome")]
+    pub(super) struct WithdrawOutcomeDef {
+        player: Address,
+        #[serde(with = "js")]
+        amount: TokenAmount,
+        error: Option<GameError>,
+        #[serde(with = "js")]
+        todays_minted: TokenAmount,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "ContentionReport")]
+    pub(super) struct ContentionReportDef {
+        #[serde(with = "js_seq")]
+        outcomes: Vec<WithdrawOutcome>,
+        #[serde(with = "js")]
+        minted: TokenAmount,
+        reverted: u64,
+        #[serde(with = "js")]
+        reverted_amount: TokenAmount,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "MonteCarloReport")]
+    pub(super) struct MonteCarloReportDef {
+        params: MonteCarloParams,
+        mean_reward: f64,
+        reward: Percentiles,
+        criticals: Percentiles,
+        pity_resets: Percentiles,
+        longest_dry_streak: Percentiles,
+        gems: GemCounts,
+        #[serde(with = "js")]
+        max_tap_reward: TokenAmount,
+        worst_dry_streak: u64,
+        reward_histogram: Histogram,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "UpgradeStep")]
+    pub(super) struct UpgradeStepDef {
+        level: u32,
+        #[serde(with = "js")]
+        cost: TokenAmount,
+        #[serde(with = "js")]
+        cumulative_cost: TokenAmount,
+        total_power: u128,
+        #[serde(with = "js")]
+        base_tap_reward: TokenAmount,
+        expected_reward_per_tap: f64,
+        extra_reward_per_tap: f64,
+        taps_to_afford: Option<u64>,
+        seconds_to_afford: Option<f64>,
+        payback_taps: Option<f64>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "UpgradePlan")]
+    pub(super) struct UpgradePlanDef {
+        current_level: u32,
+        target_level: u32,
+        total_power: u128,
+        expected_reward_per_tap: f64,
+        #[serde(with = "js")]
+        total_cost: TokenAmount,
+        #[serde(with = "js_seq")]
+        steps: Vec<UpgradeStep>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "ContractEvent", tag = "event")]
+    pub(super) enum ContractEventDef {
+        Tapped {
+            player: Address,
+            taps: u64,
+            #[serde(with = "js")]
+            reward: TokenAmount,
+            critical: bool,
+        },
+        GemFound {
+            player: Address,
+            gem_type: GemType,
+            #[serde(with = "js")]
+            bonus: TokenAmount,
+        },
+        Withdrawn {
+            player: Address,
+            #[serde(with = "js")]
+            amount: TokenAmount,
+        },
+        MinerRegistered {
+            player: Address,
+            token_id: u64,
+        },
+        MinerUnregistered {
+            player: Address,
+            token_id: u64,
+        },
+        PlayerUpgraded {
+            player: Address,
+            new_level: u32,
+            #[serde(with = "js")]
+            cost: TokenAmount,
+        },
+        CommitmentMade {
+            player: Address,
+            commitment: H256,
+        },
+        CommitmentRevealed {
+            player: Address,
+            #[serde(with = "js")]
+            reward: TokenAmount,
+        },
+        EmergencyPause {
+            caller: Address,
+        },
+        DailyLimitUpdated {
+            #[serde(with = "js")]
+            new_limit: TokenAmount,
+        },
+        MinerMinted {
+            to: Address,
+            token_id: u64,
+            rarity: Rarity,
+            #[serde(with = "js")]
+            power: U256,
+        },
+        MinerUpgraded {
+            token_id: u64,
+            #[serde(with = "js")]
+            new_power: U256,
+        },
+        MinerRenamed {
+            token_id: u64,
+            new_name: String,
+        },
+        TokensMinted {
+            to: Address,
+            #[serde(with = "js")]
+            amount: TokenAmount,
+        },
+        TokensBurned {
+            from: Address,
+            #[serde(with = "js")]
+            amount: TokenAmount,
+        },
+        Erc20Transfer {
+            from: Address,
+            to: Address,
+            #[serde(with = "js")]
+            value: TokenAmount,
+        },
+        Erc721Transfer {
+            from: Address,
+            to: Address,
+            token_id: u64,
+        },
+    }
+}
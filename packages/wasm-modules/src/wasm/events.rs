@@ -0,0 +1,34 @@
+//! The log decoder exports.
+
+use primitive_types::H256;
+use wasm_bindgen::prelude::*;
+
+use crate::commit::parse_hash;
+use crate::error::GameError;
+use crate::events::{decode_log, event_topics};
+
+use super::bindings::to_js;
+use super::dto::Js;
+
+/// `ContractEvent` object for the `topics` and `data` of a log, `0x` hex as
+/// the node returns them
+#[wasm_bindgen(js_name = decode_log, unchecked_return_type = "ContractEvent")]
+pub fn decode_log_js(topics: Vec<String>, data: &str) -> Result<JsValue, JsError> {
+    let topics = topics
+        .iter()
+        .map(|topic| parse_hash(topic))
+        .collect::<Option<Vec<H256>>>()
+        .ok_or(GameError::InvalidHash)?;
+    let data = data
+        .strip_prefix("0x")
+        .and_then(|digits| hex::decode(digits).ok())
+        .ok_or(GameError::InvalidLogData)?;
+
+    to_js(&Js(&decode_log(&topics, &data)?))
+}
+
+/// `EventTopic` of every event `decode_log` knows, for log filters
+#[wasm_bindgen(js_name = event_topics, unchecked_return_type = "EventTopic[]")]
+pub fn event_topics_js() -> Result<JsValue, JsError> {
+    to_js(&event_topics())
+}
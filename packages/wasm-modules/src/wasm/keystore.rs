@@ -0,0 +1,117 @@
+//! The `Keystore` class.
+
+use wasm_bindgen::prelude::*;
+
+use crate::commit::parse_hash;
+use crate::error::GameError;
+use crate::keystore::{
+    EncryptedKeystore, Keystore, KeystoreError, KeystoreKey, DEFAULT_PBKDF2_ITERATIONS,
+};
+
+use super::bindings::{from_js, to_js};
+use super::game_config_from_js;
+
+fn parse_signature_hex(signature: &str) -> Result<Vec<u8>, KeystoreError> {
+    signature
+        .strip_prefix("0x")
+        .and_then(|digits| hex::decode(digits).ok())
+        .ok_or(KeystoreError::InvalidSignature)
+}
+
+/// [`Keystore`] exported as the `Keystore` class
+#[wasm_bindgen(js_name = Keystore)]
+pub struct JsKeystore(Keystore);
+
+#[wasm_bindgen(js_class = Keystore)]
+impl JsKeystore {
+    /// Empty keystore under a passphrase, `iterations` defaults to 600 000
+    #[wasm_bindgen(js_name = withPassphrase)]
+    pub fn with_passphrase_js(
+        passphrase: &str,
+        iterations: Option<u32>,
+    ) -> Result<JsKeystore, JsError> {
+        let iterations = iterations.unwrap_or(DEFAULT_PBKDF2_ITERATIONS);
+        Ok(JsKeystore(Keystore::new(KeystoreKey::from_passphrase(
+            passphrase, iterations,
+        )?)))
+    }
+
+    /// Empty keystore under a hex wallet signature
+    #[wasm_bindgen(js_name = withSignature)]
+    pub fn with_signature_js(signature: &str) -> Result<JsKeystore, JsError> {
+        let signature = parse_signature_hex(signature)?;
+        Ok(JsKeystore(Keystore::new(KeystoreKey::from_signature(
+            &signature,
+        )?)))
+    }
+
+    /// Open a JSON envelope from `seal` with its passphrase
+    #[wasm_bindgen(js_name = openWithPassphrase)]
+    pub fn open_with_passphrase_js(json: &str, passphrase: &str) -> Result<JsKeystore, JsError> {
+        let envelope: EncryptedKeystore = serde_json::from_str(json)?;
+        Ok(JsKeystore(Keystore::open(
+            &envelope,
+            passphrase.as_bytes(),
+        )?))
+    }
+
+    /// Open a JSON envelope from `seal` with its hex wallet signature
+    #[wasm_bindgen(js_name = openWithSignature)]
+    pub fn open_with_signature_js(json: &str, signature: &str) -> Result<JsKeystore, JsError> {
+        let envelope: EncryptedKeystore = serde_json::from_str(json)?;
+        let signature = parse_signature_hex(signature)?;
+        Ok(JsKeystore(Keystore::open(&envelope, &signature)?))
+    }
+
+    /// Encrypted JSON envelope, the only form that should be persisted
+    #[wasm_bindgen(js_name = seal)]
+    pub fn seal_js(&self) -> Result<String, JsError> {
+        Ok(serde_json::to_string(&self.0.seal()?)?)
+    }
+
+    /// All pending `CommitmentBundle` objects
+    #[wasm_bindgen(getter, js_name = entries, unchecked_return_type = "CommitmentBundle[]")]
+    pub fn entries_js(&self) -> Result<JsValue, JsError> {
+        to_js(self.0.entries())
+    }
+
+    #[wasm_bindgen(js_name = insert)]
+    pub fn insert_js(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "CommitmentBundle")] bundle: JsValue,
+    ) -> Result<(), JsError> {
+        self.0.insert(from_js(bundle)?);
+        Ok(())
+    }
+
+    /// Bundle for a commitment hash, `null` if unknown
+    #[wasm_bindgen(js_name = get, unchecked_return_type = "CommitmentBundle | null")]
+    pub fn get_js(&self, hash: &str) -> Result<JsValue, JsError> {
+        let hash = parse_hash(hash).ok_or(GameError::InvalidHash)?;
+        to_js(&self.0.get(&hash))
+    }
+
+    #[wasm_bindgen(js_name = remove, unchecked_return_type = "CommitmentBundle | null")]
+    pub fn remove_js(&mut self, hash: &str) -> Result<JsValue, JsError> {
+        let hash = parse_hash(hash).ok_or(GameError::InvalidHash)?;
+        to_js(&self.0.remove(&hash))
+    }
+
+    /// Record the block `commitTap` was mined in, false for an unknown hash
+    #[wasm_bindgen(js_name = markCommitted)]
+    pub fn mark_committed_js(&mut self, hash: &str, block_number: u64) -> Result<bool, JsError> {
+        let hash = parse_hash(hash).ok_or(GameError::InvalidHash)?;
+        Ok(self.0.mark_committed(&hash, block_number))
+    }
+
+    /// Remove bundles whose reveal window closed, returns them
+    #[wasm_bindgen(js_name = prune, unchecked_return_type = "CommitmentBundle[]")]
+    pub fn prune_js(
+        &mut self,
+        block_number: u64,
+        #[wasm_bindgen(unchecked_param_type = "Partial<GameConfig> | undefined")] config: JsValue,
+    ) -> Result<JsValue, JsError> {
+        let config = game_config_from_js(config)?;
+        to_js(&self.0.prune(&config, block_number))
+    }
+}
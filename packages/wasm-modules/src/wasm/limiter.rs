@@ -0,0 +1,111 @@
+//! The `DailyMintLimiter` class.
+
+use wasm_bindgen::prelude::*;
+
+use crate::amount::TokenAmount;
+use crate::limiter::{DailyMintLimiter, WithdrawRequest};
+
+use super::amount::AmountJs;
+use super::bindings::{from_js, to_js};
+use super::dto::Js;
+use super::game_config_from_js;
+
+/// [`DailyMintLimiter`] exported as the `DailyMintLimiter` class
+#[wasm_bindgen(js_name = DailyMintLimiter)]
+pub struct JsDailyMintLimiter(DailyMintLimiter);
+
+#[wasm_bindgen(js_class = DailyMintLimiter)]
+impl JsDailyMintLimiter {
+    /// Limiter with nothing minted yet, with an optional `GameConfig` object
+    #[wasm_bindgen(constructor)]
+    pub fn new_js(
+        timestamp: u64,
+        #[wasm_bindgen(unchecked_param_type = "Partial<GameConfig> | undefined")] config: JsValue,
+    ) -> Result<JsDailyMintLimiter, JsError> {
+        Ok(JsDailyMintLimiter(DailyMintLimiter::new(
+            &game_config_from_js(config)?,
+            timestamp,
+        )))
+    }
+
+    /// Mintable amount left today, as a `BigInt`
+    #[wasm_bindgen(js_name = remaining, unchecked_return_type = "bigint")]
+    pub fn remaining_js(&self, timestamp: u64) -> JsValue {
+        self.0.remaining(timestamp).to_js()
+    }
+
+    /// Throws the error code `withdraw` would revert with, if any
+    #[wasm_bindgen(js_name = check)]
+    pub fn check_js(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "Amount")] amount: JsValue,
+        timestamp: u64,
+    ) -> Result<(), JsError> {
+        Ok(self.0.check(TokenAmount::from_js(&amount)?, timestamp)?)
+    }
+
+    /// `WithdrawAvailability` object for a pending amount
+    #[wasm_bindgen(js_name = availability, unchecked_return_type = "WithdrawAvailability")]
+    pub fn availability_js(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "Amount")] amount: JsValue,
+        timestamp: u64,
+    ) -> Result<JsValue, JsError> {
+        let availability = self
+            .0
+            .availability(TokenAmount::from_js(&amount)?, timestamp);
+        to_js(&availability)
+    }
+
+    #[wasm_bindgen(js_name = withdraw)]
+    pub fn withdraw_js(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "Amount")] amount: JsValue,
+        timestamp: u64,
+    ) -> Result<(), JsError> {
+        Ok(self.0.withdraw(TokenAmount::from_js(&amount)?, timestamp)?)
+    }
+
+    #[wasm_bindgen(js_name = recordWithdrawn)]
+    pub fn record_withdrawn_js(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "Amount")] amount: JsValue,
+        timestamp: u64,
+    ) -> Result<(), JsError> {
+        Ok(self
+            .0
+            .record_withdrawn(TokenAmount::from_js(&amount)?, timestamp)?)
+    }
+
+    #[wasm_bindgen(js_name = setDailyMintLimit)]
+    pub fn set_daily_mint_limit_js(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "Amount")] limit: JsValue,
+    ) -> Result<(), JsError> {
+        Ok(self.0.set_daily_mint_limit(TokenAmount::from_js(&limit)?)?)
+    }
+
+    /// Replace the whole state with a JSON string from `exportState`
+    #[wasm_bindgen(js_name = loadState)]
+    pub fn load_state_js(&mut self, json: &str) -> Result<(), JsError> {
+        self.0 = serde_json::from_str(json)?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = exportState)]
+    pub fn export_state_js(&self) -> Result<String, JsError> {
+        Ok(serde_json::to_string(&self.0)?)
+    }
+
+    /// `ContentionReport` for an array of `WithdrawRequest` objects
+    #[wasm_bindgen(js_name = simulate, unchecked_return_type = "ContentionReport")]
+    pub fn simulate_js(
+        &self,
+        #[wasm_bindgen(unchecked_param_type = "WithdrawRequest[]")] requests: JsValue,
+    ) -> Result<JsValue, JsError> {
+        let requests: Vec<Js<WithdrawRequest>> = from_js(requests)?;
+        let requests: Vec<WithdrawRequest> =
+            requests.into_iter().map(|Js(request)| request).collect();
+        to_js(&Js(&self.0.simulate(&requests)?))
+    }
+}
@@ -0,0 +1,274 @@
+//! wasm-bindgen exports of the mechanics in [`crate::core`].
+//!
+//! Every `#[wasm_bindgen]` function and class lives under this module, one
+//! file per core module, and only converts its arguments and results with
+//! [`bindings`], through the `dto` definitions for types that carry amounts.
+//! Classes are newtypes around the core type, exported under its name.
+//! Built with the `wasm` feature.
+
+use primitive_types::U256;
+use wasm_bindgen::prelude::*;
+
+use crate::address::Address;
+use crate::amount::TokenAmount;
+use crate::config::GameConfig;
+use crate::error::GameError;
+use crate::{commit, gems, player, power, reveal, taps};
+
+use amount::AmountJs;
+use bindings::{from_js, to_js};
+use dto::Js;
+
+mod amount;
+mod batcher;
+pub mod bindings;
+mod derive;
+mod distribution;
+mod dto;
+mod events;
+mod keystore;
+mod limiter;
+mod montecarlo;
+mod pity;
+mod simulator;
+mod tracker;
+mod upgrade;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console)]
+    fn log(s: &str);
+}
+
+/// Predict reward for a given tap without blockchain call
+/// Uses local PRNG seeded with user address and block number
+/// `base_reward` is the per-tap reward in wei, as a `BigInt`
+/// `config` is an optional `GameConfig` object, defaults to the deployed contract
+#[wasm_bindgen(unchecked_return_type = "RewardPrediction")]
+pub fn predict_tap_reward(
+    user_address: &str,
+    #[wasm_bindgen(unchecked_param_type = "Amount")] base_reward: JsValue,
+    taps_since_critical: u32,
+    block_number: u64,
+    nonce: u32,
+    #[wasm_bindgen(unchecked_param_type = "Partial<GameConfig> | undefined")] config: JsValue,
+) -> Result<JsValue, JsError> {
+    let config = game_config_from_js(config)?;
+    let base_reward = TokenAmount::from_js(&base_reward)?;
+
+    let prediction = taps::predict_tap_reward(
+        &config,
+        user_address,
+        base_reward,
+        taps_since_critical as u64,
+        block_number,
+        nonce,
+    )?;
+    to_js(&Js(&prediction))
+}
+
+/// Parse an optional `GameConfig` object, `undefined` means the deployed defaults
+pub(crate) fn game_config_from_js(config: JsValue) -> Result<GameConfig, JsError> {
+    if config.is_undefined() || config.is_null() {
+        return Ok(GameConfig::default());
+    }
+
+    let Js(config): Js<GameConfig> = from_js(config)?;
+    config.validate()?;
+    Ok(config)
+}
+
+/// Error code for a revert reason returned by the node, e.g. "Too early" -> "TOO_EARLY"
+#[wasm_bindgen]
+pub fn error_code_from_revert(reason: &str) -> Option<String> {
+    GameError::from_reason(reason).map(|error| error.code().to_string())
+}
+
+/// Contract revert reason behind an error code thrown by this module
+#[wasm_bindgen]
+pub fn error_revert_reason(code: &str) -> Option<String> {
+    GameError::from_code(code)
+        .and_then(|error| error.reason())
+        .map(str::to_string)
+}
+
+/// Game parameters of the deployed contract, as a `GameConfig` object
+#[wasm_bindgen(unchecked_return_type = "GameConfig")]
+pub fn default_game_config() -> Result<JsValue, JsError> {
+    to_js(&Js(&GameConfig::default()))
+}
+
+/// `GameParams` summary of a config, the deployed one by default
+#[wasm_bindgen(unchecked_return_type = "GameParams")]
+pub fn game_params(
+    #[wasm_bindgen(unchecked_param_type = "Partial<GameConfig> | undefined")] config: JsValue,
+) -> Result<JsValue, JsError> {
+    to_js(&Js(&game_config_from_js(config)?.params()))
+}
+
+/// `PlayerStats` of a `PlayerData` object, without the miner list
+#[wasm_bindgen(unchecked_return_type = "PlayerStatsSummary")]
+pub fn player_stats(
+    user_address: &str,
+    #[wasm_bindgen(unchecked_param_type = "PlayerData")] player: JsValue,
+) -> Result<JsValue, JsError> {
+    let Js(player): Js<player::PlayerData> = from_js(player)?;
+    to_js(&Js(&player.stats(user_address.parse()?)))
+}
+
+/// Chance of finding each gem on the next tap and over a batch of `taps`
+#[wasm_bindgen(unchecked_return_type = "GemDropProbabilities")]
+pub fn gem_drop_probabilities(
+    taps_since_critical: u64,
+    taps: u32,
+    #[wasm_bindgen(unchecked_param_type = "Partial<GameConfig> | undefined")] config: JsValue,
+) -> Result<JsValue, JsError> {
+    let config = game_config_from_js(config)?;
+    to_js(&gems::gem_drop_probabilities(
+        &config,
+        taps_since_critical,
+        taps,
+    ))
+}
+
+/// Replay the contract's per-tap seed chain for a known initial seed
+/// `initial_seed` is hex, `base_tap_reward` is a wei amount as a `BigInt`
+#[wasm_bindgen(unchecked_return_type = "TapExecution")]
+pub fn execute_taps(
+    initial_seed: &str,
+    taps: u16,
+    taps_since_critical: u64,
+    #[wasm_bindgen(unchecked_param_type = "Amount")] base_tap_reward: JsValue,
+    #[wasm_bindgen(unchecked_param_type = "Partial<GameConfig> | undefined")] config: JsValue,
+) -> Result<JsValue, JsError> {
+    let config = game_config_from_js(config)?;
+    let seed = taps::parse_seed(initial_seed).ok_or(GameError::InvalidSeed)?;
+    let base_tap_reward = TokenAmount::from_js(&base_tap_reward)?;
+
+    let execution = taps::execute_taps(&config, seed, taps, taps_since_critical, base_tap_reward)?;
+    to_js(&Js(&execution))
+}
+
+/// Compute the exact outcome of `revealTap` before sending it
+/// `block_hash` is the hash of block `commitBlock + 1`, `player` a `PlayerData` object
+#[wasm_bindgen(unchecked_return_type = "RevealOutcome")]
+pub fn compute_reveal_outcome(
+    block_hash: &str,
+    user_address: &str,
+    secret: &str,
+    nonce: &str,
+    taps: u16,
+    #[wasm_bindgen(unchecked_param_type = "PlayerData")] player: JsValue,
+    #[wasm_bindgen(unchecked_param_type = "Partial<GameConfig> | undefined")] config: JsValue,
+) -> Result<JsValue, JsError> {
+    let config = game_config_from_js(config)?;
+    let block_hash = commit::parse_hash(block_hash).ok_or(GameError::InvalidHash)?;
+    let (player_address, secret, nonce) = parse_commit_inputs(user_address, secret, nonce)?;
+    let Js(player): Js<player::PlayerData> = from_js(player)?;
+
+    let outcome = reveal::compute_reveal(
+        &config,
+        &block_hash,
+        &player_address,
+        secret,
+        nonce,
+        taps,
+        &player,
+    )?;
+    to_js(&Js(&outcome))
+}
+
+/// Build the commitment hash for `commitTap`
+/// Same layout as `revealTap`: address, uint256 secret, uint256 nonce, uint128 taps
+#[wasm_bindgen]
+pub fn hash_tap_commit(
+    user_address: &str,
+    secret: &str,
+    nonce: &str,
+    taps: u16,
+) -> Result<String, JsError> {
+    let (player, secret, nonce) = parse_commit_inputs(user_address, secret, nonce)?;
+    let hash = commit::commitment_hash(&player, secret, nonce, taps);
+    Ok(format!("{:#x}", hash))
+}
+
+/// Draw a fresh secret and nonce and build the commitment for `commitTap`
+/// Returns a `CommitmentBundle` object to keep until `revealTap`
+#[wasm_bindgen(unchecked_return_type = "CommitmentBundle")]
+pub fn new_commitment(
+    user_address: &str,
+    taps: u16,
+    block_number: u64,
+    #[wasm_bindgen(unchecked_param_type = "Partial<GameConfig> | undefined")] config: JsValue,
+) -> Result<JsValue, JsError> {
+    let config = game_config_from_js(config)?;
+    config.check_tap_count(taps)?;
+    let player: Address = user_address.parse()?;
+
+    let bundle = commit::CommitmentBundle::generate(player, taps, block_number)?;
+    to_js(&bundle)
+}
+
+/// Check a commitment against its opening before sending `revealTap`
+#[wasm_bindgen]
+pub fn verify_tap_commit(
+    commitment: &str,
+    user_address: &str,
+    secret: &str,
+    nonce: &str,
+    taps: u16,
+) -> Result<bool, JsError> {
+    let commitment = commit::parse_hash(commitment).ok_or(GameError::InvalidHash)?;
+    let (player, secret, nonce) = parse_commit_inputs(user_address, secret, nonce)?;
+    Ok(commit::verify_commitment(
+        &commitment,
+        &player,
+        secret,
+        nonce,
+        taps,
+    ))
+}
+
+fn parse_commit_inputs(
+    user_address: &str,
+    secret: &str,
+    nonce: &str,
+) -> Result<(Address, U256, U256), JsError> {
+    let player: Address = user_address.parse()?;
+    let secret = commit::parse_word(secret).ok_or(GameError::InvalidSecret)?;
+    let nonce = commit::parse_word(nonce).ok_or(GameError::InvalidNonce)?;
+    Ok((player, secret, nonce))
+}
+
+/// Calculate total power from the powers of owned miners and the player level
+/// Same as the contract: sum of miner powers times `player_level + 1`, as a `BigInt`
+#[wasm_bindgen(unchecked_return_type = "bigint")]
+pub fn calculate_total_power(base_powers: Vec<u64>, player_level: u32) -> Result<JsValue, JsError> {
+    let powers: Vec<u128> = base_powers.into_iter().map(u128::from).collect();
+    let total = power::total_power(&powers, player_level)?;
+    Ok(JsValue::bigint_from_str(&total.to_string()))
+}
+
+/// Recompute power and per-tap base reward exactly like `_executeTaps`
+/// `player` is a `PlayerData` object, `miners` an array of `MinerInfo` objects
+#[wasm_bindgen(unchecked_return_type = "PowerBreakdown")]
+pub fn calculate_power(
+    user_address: &str,
+    #[wasm_bindgen(unchecked_param_type = "PlayerData")] player: JsValue,
+    #[wasm_bindgen(unchecked_param_type = "MinerInfo[]")] miners: JsValue,
+    commit_reveal: bool,
+    #[wasm_bindgen(unchecked_param_type = "Partial<GameConfig> | undefined")] config: JsValue,
+) -> Result<JsValue, JsError> {
+    let config = game_config_from_js(config)?;
+    let player_address: Address = user_address.parse()?;
+    let Js(player): Js<player::PlayerData> = from_js(player)?;
+    let miners: Vec<power::MinerInfo> = from_js(miners)?;
+    let path = if commit_reveal {
+        power::TapPath::CommitReveal
+    } else {
+        power::TapPath::TapMine
+    };
+
+    let breakdown = power::calculate_power(&config, &player_address, &player, &miners, path)?;
+    to_js(&Js(&breakdown))
+}
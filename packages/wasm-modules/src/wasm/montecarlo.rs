@@ -0,0 +1,25 @@
+//! The `simulate_sessions` export.
+
+use wasm_bindgen::prelude::*;
+
+use crate::montecarlo::{simulate_sessions, MonteCarloParams};
+
+use super::bindings::{from_js, to_js};
+use super::dto::Js;
+use super::game_config_from_js;
+
+/// `MonteCarloReport` object for a `MonteCarloParams` object, missing fields
+/// taking their defaults
+#[wasm_bindgen(js_name = simulate_sessions, unchecked_return_type = "MonteCarloReport")]
+pub fn simulate_sessions_js(
+    #[wasm_bindgen(unchecked_param_type = "Partial<MonteCarloParams> | undefined")] params: JsValue,
+    #[wasm_bindgen(unchecked_param_type = "Partial<GameConfig> | undefined")] config: JsValue,
+) -> Result<JsValue, JsError> {
+    let config = game_config_from_js(config)?;
+    let params: MonteCarloParams = if params.is_undefined() || params.is_null() {
+        MonteCarloParams::default()
+    } else {
+        from_js(params)?
+    };
+    to_js(&Js(&simulate_sessions(&config, &params)?))
+}
@@ -0,0 +1,61 @@
+//! The `PityChain` class.
+
+use wasm_bindgen::prelude::*;
+
+use crate::pity::PityChain;
+use crate::power::{base_tap_reward, TapPath};
+
+use super::bindings::to_js;
+use super::game_config_from_js;
+
+/// [`PityChain`] exported as the `PityChain` class
+#[wasm_bindgen(js_name = PityChain)]
+pub struct JsPityChain(PityChain);
+
+#[wasm_bindgen(js_class = PityChain)]
+impl JsPityChain {
+    #[wasm_bindgen(constructor)]
+    pub fn new_js(
+        #[wasm_bindgen(unchecked_param_type = "Partial<GameConfig> | undefined")] config: JsValue,
+    ) -> Result<JsPityChain, JsError> {
+        Ok(JsPityChain(PityChain::new(game_config_from_js(config)?)))
+    }
+
+    #[wasm_bindgen(getter, js_name = states)]
+    pub fn states_js(&self) -> usize {
+        self.0.states()
+    }
+
+    /// `TapsUntilCritical` object, listing up to `max_taps` taps
+    #[wasm_bindgen(js_name = tapsUntilCritical, unchecked_return_type = "TapsUntilCritical")]
+    pub fn taps_until_critical_js(
+        &self,
+        taps_since_critical: u64,
+        max_taps: u32,
+    ) -> Result<JsValue, JsError> {
+        to_js(&self.0.taps_until_critical(taps_since_critical, max_taps))
+    }
+
+    #[wasm_bindgen(js_name = criticalCounts)]
+    pub fn critical_counts_js(&self, taps_since_critical: u64, taps: u32) -> Vec<f64> {
+        self.0.critical_counts(taps_since_critical, taps)
+    }
+
+    /// `SessionForecast` object for `total_power` (a `BigInt`)
+    #[wasm_bindgen(js_name = forecast, unchecked_return_type = "SessionForecast")]
+    pub fn forecast_js(
+        &self,
+        taps_since_critical: u64,
+        taps: u32,
+        total_power: u128,
+        commit_reveal: bool,
+    ) -> Result<JsValue, JsError> {
+        let path = if commit_reveal {
+            TapPath::CommitReveal
+        } else {
+            TapPath::TapMine
+        };
+        let base = base_tap_reward(self.0.config(), total_power, path)?;
+        to_js(&self.0.forecast(taps_since_critical, taps, base)?)
+    }
+}
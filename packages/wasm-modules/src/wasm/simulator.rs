@@ -0,0 +1,150 @@
+//! The `PlayerSimulator` class.
+
+use wasm_bindgen::prelude::*;
+
+use crate::amount::TokenAmount;
+use crate::commit::{parse_hash, parse_word};
+use crate::error::GameError;
+use crate::power::MinerInfo;
+use crate::simulator::PlayerSimulator;
+use crate::taps::parse_seed;
+
+use super::amount::AmountJs;
+use super::bindings::to_js;
+use super::dto::Js;
+use super::game_config_from_js;
+
+/// [`PlayerSimulator`] exported as the `PlayerSimulator` class
+#[wasm_bindgen(js_name = PlayerSimulator)]
+pub struct JsPlayerSimulator(PlayerSimulator);
+
+#[wasm_bindgen(js_class = PlayerSimulator)]
+impl JsPlayerSimulator {
+    /// Create a simulator for `user_address`, with an optional `GameConfig` object
+    #[wasm_bindgen(constructor)]
+    pub fn new_js(
+        user_address: &str,
+        #[wasm_bindgen(unchecked_param_type = "Partial<GameConfig> | undefined")] config: JsValue,
+    ) -> Result<JsPlayerSimulator, JsError> {
+        let config = game_config_from_js(config)?;
+        Ok(JsPlayerSimulator(PlayerSimulator::new(
+            config,
+            user_address.parse()?,
+        )))
+    }
+
+    /// Current `PlayerData` as an object
+    #[wasm_bindgen(getter, js_name = player, unchecked_return_type = "PlayerData")]
+    pub fn player_js(&self) -> Result<JsValue, JsError> {
+        to_js(&Js(&self.0.state().player))
+    }
+
+    /// `PlayerStats` of the simulated player, without the miner list
+    #[wasm_bindgen(getter, js_name = stats, unchecked_return_type = "PlayerStatsSummary")]
+    pub fn stats_js(&self) -> Result<JsValue, JsError> {
+        to_js(&Js(&self.0.state().player.stats(self.0.state().address)))
+    }
+
+    /// Wallet balance as a `BigInt`
+    #[wasm_bindgen(getter, js_name = balance, unchecked_return_type = "bigint")]
+    pub fn balance_js(&self) -> JsValue {
+        self.0.state().balance.to_js()
+    }
+
+    #[wasm_bindgen(js_name = setBalance)]
+    pub fn set_balance_js(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "Amount")] balance: JsValue,
+    ) -> Result<(), JsError> {
+        self.0.set_balance(TokenAmount::from_js(&balance)?);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = setMiner)]
+    pub fn set_miner_js(&mut self, token_id: u64, owner: &str, power: u128) -> Result<(), JsError> {
+        self.0.set_miner(MinerInfo {
+            token_id,
+            owner: owner.parse()?,
+            power,
+        });
+        Ok(())
+    }
+
+    /// `tapMine` with a hex seed, returns the tap execution
+    #[wasm_bindgen(js_name = tap, unchecked_return_type = "TapExecution")]
+    pub fn tap_js(&mut self, seed: &str, taps: u16, block_number: u64) -> Result<JsValue, JsError> {
+        let seed = parse_seed(seed).ok_or(GameError::InvalidSeed)?;
+        let execution = self.0.tap(seed, taps, block_number)?;
+        to_js(&Js(&execution))
+    }
+
+    /// `commitTap` of a hex commitment hash, mined in `block_number`
+    #[wasm_bindgen(js_name = commit)]
+    pub fn commit_js(
+        &mut self,
+        commitment: &str,
+        taps: u16,
+        block_number: u64,
+    ) -> Result<(), JsError> {
+        let commitment = parse_hash(commitment).ok_or(GameError::InvalidHash)?;
+        Ok(self.0.commit(commitment, taps, block_number)?)
+    }
+
+    /// `CommitStatus` object of the pending commitment at `block_number`
+    #[wasm_bindgen(js_name = commitStatus, unchecked_return_type = "CommitStatus")]
+    pub fn commit_status_js(&self, block_number: u64) -> Result<JsValue, JsError> {
+        to_js(&self.0.commit_status(block_number))
+    }
+
+    /// `revealTap` of the pending commitment, with the hash of block `commitBlock + 1`
+    #[wasm_bindgen(js_name = reveal, unchecked_return_type = "TapExecution")]
+    pub fn reveal_js(
+        &mut self,
+        block_hash: &str,
+        secret: &str,
+        nonce: &str,
+        block_number: u64,
+    ) -> Result<JsValue, JsError> {
+        let block_hash = parse_hash(block_hash).ok_or(GameError::InvalidHash)?;
+        let secret = parse_word(secret).ok_or(GameError::InvalidSecret)?;
+        let nonce = parse_word(nonce).ok_or(GameError::InvalidNonce)?;
+
+        let execution = self.0.reveal(&block_hash, secret, nonce, block_number)?;
+        to_js(&Js(&execution))
+    }
+
+    /// `withdraw` in a block at `timestamp`, returns the withdrawn amount as a `BigInt`
+    #[wasm_bindgen(js_name = withdraw, unchecked_return_type = "bigint")]
+    pub fn withdraw_js(&mut self, timestamp: u64) -> Result<JsValue, JsError> {
+        Ok(self.0.withdraw(timestamp)?.to_js())
+    }
+
+    /// `upgrade`, returns the burned cost as a `BigInt`
+    #[wasm_bindgen(js_name = upgrade, unchecked_return_type = "bigint")]
+    pub fn upgrade_js(&mut self) -> Result<JsValue, JsError> {
+        Ok(self.0.upgrade()?.to_js())
+    }
+
+    #[wasm_bindgen(js_name = registerMiner)]
+    pub fn register_miner_js(&mut self, token_id: u64) -> Result<(), JsError> {
+        Ok(self.0.register_miner(token_id)?)
+    }
+
+    #[wasm_bindgen(js_name = unregisterMiner)]
+    pub fn unregister_miner_js(&mut self, token_id: u64) -> Result<(), JsError> {
+        Ok(self.0.unregister_miner(token_id)?)
+    }
+
+    /// Replace the whole state with a JSON string from `exportState`
+    #[wasm_bindgen(js_name = loadState)]
+    pub fn load_state_js(&mut self, json: &str) -> Result<(), JsError> {
+        self.0.set_state(serde_json::from_str(json)?);
+        Ok(())
+    }
+
+    /// Serialize the whole state to JSON
+    #[wasm_bindgen(js_name = exportState)]
+    pub fn export_state_js(&self) -> Result<String, JsError> {
+        Ok(serde_json::to_string(self.0.state())?)
+    }
+}
@@ -0,0 +1,77 @@
+//! The `CommitTracker` class.
+
+use wasm_bindgen::prelude::*;
+
+use crate::address::Address;
+use crate::commit::{parse_hash, parse_word};
+use crate::error::GameError;
+use crate::tracker::{CommitTracker, Commitment};
+
+use super::bindings::{from_js, to_js};
+use super::game_config_from_js;
+
+/// [`CommitTracker`] exported as the `CommitTracker` class
+#[wasm_bindgen(js_name = CommitTracker)]
+pub struct JsCommitTracker(CommitTracker);
+
+#[wasm_bindgen(js_class = CommitTracker)]
+impl JsCommitTracker {
+    /// Create a tracker with no commitment, with an optional `GameConfig` object
+    #[wasm_bindgen(constructor)]
+    pub fn new_js(
+        #[wasm_bindgen(unchecked_param_type = "Partial<GameConfig> | undefined")] config: JsValue,
+    ) -> Result<JsCommitTracker, JsError> {
+        Ok(JsCommitTracker(CommitTracker::new(game_config_from_js(
+            config,
+        )?)))
+    }
+
+    /// Current `Commitment` object, `null` when idle
+    #[wasm_bindgen(getter, js_name = commitment, unchecked_return_type = "Commitment | null")]
+    pub fn commitment_js(&self) -> Result<JsValue, JsError> {
+        to_js(&self.0.commitment())
+    }
+
+    /// Replace the tracked commitment with the result of `getCommitment`
+    #[wasm_bindgen(js_name = setCommitment)]
+    pub fn set_commitment_js(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "Commitment | null")] commitment: JsValue,
+    ) -> Result<(), JsError> {
+        let commitment: Option<Commitment> = from_js(commitment)?;
+        self.0.set_commitment(commitment);
+        Ok(())
+    }
+
+    /// `CommitStatus` object for a transaction mined in `block_number`
+    #[wasm_bindgen(js_name = status, unchecked_return_type = "CommitStatus")]
+    pub fn status_js(&self, block_number: u64) -> Result<JsValue, JsError> {
+        to_js(&self.0.status(block_number))
+    }
+
+    #[wasm_bindgen(js_name = commit)]
+    pub fn commit_js(&mut self, hash: &str, taps: u16, block_number: u64) -> Result<(), JsError> {
+        let hash = parse_hash(hash).ok_or(GameError::InvalidHash)?;
+        Ok(self.0.commit(hash, taps, block_number)?)
+    }
+
+    /// Throws the error code `revealTap` would revert with, if any
+    #[wasm_bindgen(js_name = checkReveal)]
+    pub fn check_reveal_js(&self, block_number: u64) -> Result<(), JsError> {
+        Ok(self.0.check_reveal(block_number)?)
+    }
+
+    #[wasm_bindgen(js_name = reveal)]
+    pub fn reveal_js(
+        &mut self,
+        user_address: &str,
+        secret: &str,
+        nonce: &str,
+        block_number: u64,
+    ) -> Result<u16, JsError> {
+        let player: Address = user_address.parse()?;
+        let secret = parse_word(secret).ok_or(GameError::InvalidSecret)?;
+        let nonce = parse_word(nonce).ok_or(GameError::InvalidNonce)?;
+        Ok(self.0.reveal(&player, secret, nonce, block_number)?)
+    }
+}
@@ -0,0 +1,45 @@
+//! The `plan_upgrades` export.
+
+use wasm_bindgen::prelude::*;
+
+use crate::amount::TokenAmount;
+use crate::power::TapPath;
+use crate::upgrade::plan_upgrades;
+
+use super::amount::AmountJs;
+use super::bindings::to_js;
+use super::dto::Js;
+use super::game_config_from_js;
+
+/// `UpgradePlan` object from `current_level` to `target_level`
+/// `balance` is a wei `BigInt`, `miner_powers` the powers of owned registered miners
+#[wasm_bindgen(js_name = plan_upgrades, unchecked_return_type = "UpgradePlan")]
+pub fn plan_upgrades_js(
+    current_level: u32,
+    target_level: u32,
+    #[wasm_bindgen(unchecked_param_type = "Amount")] balance: JsValue,
+    miner_powers: Vec<u64>,
+    taps_per_minute: f64,
+    commit_reveal: bool,
+    #[wasm_bindgen(unchecked_param_type = "Partial<GameConfig> | undefined")] config: JsValue,
+) -> Result<JsValue, JsError> {
+    let config = game_config_from_js(config)?;
+    let balance = TokenAmount::from_js(&balance)?;
+    let powers: Vec<u128> = miner_powers.into_iter().map(u128::from).collect();
+    let path = if commit_reveal {
+        TapPath::CommitReveal
+    } else {
+        TapPath::TapMine
+    };
+
+    let plan = plan_upgrades(
+        &config,
+        current_level,
+        target_level,
+        balance,
+        &powers,
+        taps_per_minute,
+        path,
+    )?;
+    to_js(&Js(&plan))
+}